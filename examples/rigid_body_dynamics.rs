@@ -1,6 +1,6 @@
 use cyclone_physics::{
     precision::Real,
-    rigid_body::{PhysicsSystem, RigidBody, RigidBodySet},
+    rigid_body::{fgen::ForceGeneratorSet, PhysicsSystem, RigidBody, RigidBodySet},
     Mat3, Quat, Vec3,
 };
 use macroquad::prelude::{Vec3 as MVec3, *};
@@ -22,6 +22,7 @@ async fn main() {
     set_camera(&camera);
 
     let mut bodies = RigidBodySet::new();
+    let mut generators = ForceGeneratorSet::new();
     let mut system = PhysicsSystem::new();
 
     let cube = bodies.insert(
@@ -57,7 +58,7 @@ async fn main() {
             bodies[cube].add_force(BREAK_FORCE * dir);
         }
 
-        system.step(&mut bodies, get_frame_time());
+        system.step(&mut bodies, &mut generators, get_frame_time());
 
         clear_background(LIGHTGRAY);
 
@@ -13,6 +13,7 @@ const GRAVITY: Vec3 = Vec3::new(0.0, 250.0, 0.0);
 const PLAYER_DAMPING: f32 = 0.75;
 const SPRING_CONSTANT: f32 = 25.0;
 const SPRING_REST_LENGTH: f32 = 150.0;
+const SUBSTEPS: u32 = 4;
 const SPRING_THICKNESS: f32 = 3.0;
 const MOUSE_PARTICLE_RADIUS: f32 = 25.0;
 const PLAYER_PARTICLE_RADIUS: f32 = 35.0;
@@ -32,14 +33,14 @@ async fn main() {
             .with_damping(PLAYER_DAMPING),
     );
 
-    let spring = generators.insert(ParticleAnchoredSpring {
-        target: player_particle,
-        anchor: mouse_pos_vec(),
-        spring_constant: SPRING_CONSTANT,
-        rest_length: SPRING_REST_LENGTH,
-    });
+    let spring = generators.insert(ParticleAnchoredSpring::new(
+        player_particle,
+        mouse_pos_vec(),
+        SPRING_CONSTANT,
+        SPRING_REST_LENGTH,
+    ));
 
-    let mut system = ParticlePhysicsSystem::new(1024, 0);
+    let mut system = ParticlePhysicsSystem::new(1024, 0).with_substeps(SUBSTEPS);
 
     loop {
         clear_background(BLACK);
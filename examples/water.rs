@@ -0,0 +1,136 @@
+use cyclone_physics::{
+    consts::GRAVITY,
+    particle::{
+        fgen::ParticleForceGeneratorSet, sph::SphFluid, Particle, ParticleGroup,
+        ParticlePhysicsSystem, ParticleSet,
+    },
+    rigid_body::collide_narrow::Plane,
+    Vec3,
+};
+
+use macroquad::prelude::*;
+
+const SMOOTHING_RADIUS: f32 = 0.1;
+const SPACING: f32 = 0.05;
+const REST_DENSITY: f32 = 1000.0;
+const TANK_WIDTH: f32 = 2.0;
+const TANK_HEIGHT: f32 = 1.2;
+const TANK_DEPTH: f32 = 0.15;
+const COLUMNS: usize = 20;
+const ROWS: usize = 40;
+const LAYERS: usize = 3;
+const SUBSTEPS: usize = 4;
+const PIXELS_PER_METRE: f32 = 350.0;
+const PARTICLE_RADIUS: f32 = 4.0;
+const WATER_COLOR: Color = SKYBLUE;
+const FOAM_COLOR: Color = WHITE;
+const TANK_COLOR: Color = GRAY;
+
+#[macroquad::main("Water")]
+async fn main() {
+    let mut particles = ParticleSet::new();
+    let mut generators = ParticleForceGeneratorSet::new();
+
+    let water = ParticleGroup::new(0);
+
+    // Start with a column of water against the left wall of the tank.
+    for layer in 0..LAYERS {
+        for row in 0..ROWS {
+            for column in 0..COLUMNS {
+                let position = Vec3::new(
+                    column as f32 * SPACING,
+                    row as f32 * SPACING,
+                    layer as f32 * SPACING,
+                ) + Vec3::splat(SPACING / 2.0);
+
+                particles.insert(
+                    Particle::new(REST_DENSITY * SPACING * SPACING * SPACING)
+                        .with_position(position)
+                        .with_acceleration(GRAVITY)
+                        .with_damping(1.0)
+                        .with_groups(water),
+                );
+            }
+        }
+    }
+
+    let fluid = generators.insert(
+        SphFluid::new(water, SMOOTHING_RADIUS, REST_DENSITY)
+            .with_boundary(Plane {
+                normal: Vec3::Y,
+                offset: 0.0,
+            })
+            .with_boundary(Plane {
+                normal: Vec3::X,
+                offset: 0.0,
+            })
+            .with_boundary(Plane {
+                normal: Vec3::NEG_X,
+                offset: -TANK_WIDTH,
+            })
+            .with_boundary(Plane {
+                normal: Vec3::Z,
+                offset: 0.0,
+            })
+            .with_boundary(Plane {
+                normal: Vec3::NEG_Z,
+                offset: -TANK_DEPTH,
+            }),
+    );
+
+    let mut system = ParticlePhysicsSystem::new(16, 0);
+
+    loop {
+        clear_background(BLACK);
+
+        let duration = get_frame_time().min(1.0 / 30.0) / SUBSTEPS as f32;
+
+        for _ in 0..SUBSTEPS {
+            system.start_frame(&mut particles);
+            system.step(&mut particles, &mut generators, duration);
+
+            let fluid: &SphFluid = generators.get(fluid).unwrap();
+            fluid.enforce_boundaries(&mut particles);
+        }
+
+        let origin = tank_origin();
+
+        draw_rectangle_lines(
+            origin.x,
+            origin.y - TANK_HEIGHT * PIXELS_PER_METRE,
+            TANK_WIDTH * PIXELS_PER_METRE,
+            TANK_HEIGHT * PIXELS_PER_METRE,
+            2.0,
+            TANK_COLOR,
+        );
+
+        let fluid: &SphFluid = generators.get(fluid).unwrap();
+        for (id, particle) in particles.group(water) {
+            // Particles in thin spray are drawn lighter.
+            let density = fluid.density(id).unwrap_or(REST_DENSITY);
+            let foam = (1.0 - density / REST_DENSITY).clamp(0.0, 1.0);
+            let color = Color::new(
+                WATER_COLOR.r + (FOAM_COLOR.r - WATER_COLOR.r) * foam,
+                WATER_COLOR.g + (FOAM_COLOR.g - WATER_COLOR.g) * foam,
+                WATER_COLOR.b + (FOAM_COLOR.b - WATER_COLOR.b) * foam,
+                1.0,
+            );
+
+            draw_circle(
+                origin.x + particle.position.x * PIXELS_PER_METRE,
+                origin.y - particle.position.y * PIXELS_PER_METRE,
+                PARTICLE_RADIUS,
+                color,
+            );
+        }
+
+        next_frame().await;
+    }
+}
+
+fn tank_origin() -> Vec2 {
+    vec2(
+        (screen_width() - TANK_WIDTH * PIXELS_PER_METRE) / 2.0,
+        (screen_height() + TANK_HEIGHT * PIXELS_PER_METRE) / 2.0,
+    )
+}
@@ -0,0 +1,37 @@
+use crate::{precision::Real, Vec3};
+
+/// Calculates the drag force opposing the given velocity.
+pub(crate) fn drag_force(velocity: Vec3, k1: Real, k2: Real) -> Vec3 {
+    let speed = velocity.magnitude();
+    velocity * -(k1 + k2 * speed)
+}
+
+/// Returns whether the force is above the break force, for generators
+/// that have one.
+pub(crate) fn exceeds_break_force(force: Vec3, break_force: Option<Real>) -> bool {
+    break_force.is_some_and(|break_force| force.squared_magnitude() > break_force * break_force)
+}
+
+/// Calculates where the damped harmonic motion of a stiff spring
+/// takes a point at the given offset from its anchor after
+/// `duration`. Returns `None` when the motion isn't underdamped.
+pub(crate) fn stiff_spring_target(
+    offset: Vec3,
+    velocity: Vec3,
+    spring_constant: Real,
+    damping: Real,
+    duration: Real,
+) -> Option<Vec3> {
+    // Calculate the constants for the differential equation.
+    let discriminant = 4.0 * spring_constant - damping * damping;
+    if discriminant <= 0.0 || duration <= 0.0 {
+        return None;
+    }
+    let gamma = 0.5 * discriminant.sqrt();
+    let c = offset * (damping / (2.0 * gamma)) + velocity * gamma.recip();
+
+    // Calculate the target position.
+    let target = (offset * (gamma * duration).cos() + c * (gamma * duration).sin())
+        * (-0.5 * duration * damping).exp();
+    Some(target)
+}
@@ -1,8 +1,10 @@
 pub mod consts;
+mod fgen_common;
 pub mod math;
 pub mod particle;
 pub mod precision;
 pub mod rigid_body;
+pub mod timestep;
 
 pub use math::{
     matrix::{Mat3, Mat4},
@@ -1,4 +1,4 @@
-use std::ops::{Mul, MulAssign};
+use std::ops::{Add, Mul, MulAssign, Sub};
 
 use crate::{precision::Real, Vec3};
 
@@ -21,6 +21,16 @@ impl Mat3 {
         Self { data }
     }
 
+    /// Creates a skew-symmetric matrix from the given vector. The
+    /// skew-symmetric matrix is the equivalent of the vector
+    /// product, so if a and b are vectors, a x b = A_s b, where
+    /// A_s is the skew-symmetric form of a.
+    pub const fn skew_symmetric(vector: Vec3) -> Self {
+        Self::new([
+            0.0, -vector.z, vector.y, vector.z, 0.0, -vector.x, -vector.y, vector.x, 0.0,
+        ])
+    }
+
     /// Transform the given vector by this matrix.
     pub fn transform(&self, vector: Vec3) -> Vec3 {
         Vec3::new(
@@ -94,6 +104,12 @@ impl Mat3 {
             - t6 * self.data[4]
     }
 
+    /// Interpolates a couple of matrices, returning this matrix
+    /// when `t` is zero and the other one when it is one.
+    pub fn lerp(self, other: Self, t: Real) -> Self {
+        self * (1.0 - t) + other * t
+    }
+
     pub fn transpose(&self) -> Self {
         Self::new([
             self.data[0],
@@ -118,6 +134,38 @@ impl Mul<Vec3> for Mat3 {
     }
 }
 
+impl Mul<Real> for Mat3 {
+    type Output = Mat3;
+
+    /// Returns a matrix with every component multiplied by the
+    /// given scalar.
+    fn mul(self, scalar: Real) -> Self::Output {
+        Self::new(self.data.map(|component| component * scalar))
+    }
+}
+
+impl Add for Mat3 {
+    type Output = Mat3;
+
+    fn add(mut self, rhs: Mat3) -> Self::Output {
+        for (component, rhs) in self.data.iter_mut().zip(rhs.data) {
+            *component += rhs;
+        }
+        self
+    }
+}
+
+impl Sub for Mat3 {
+    type Output = Mat3;
+
+    fn sub(mut self, rhs: Mat3) -> Self::Output {
+        for (component, rhs) in self.data.iter_mut().zip(rhs.data) {
+            *component -= rhs;
+        }
+        self
+    }
+}
+
 impl Mul<Mat3> for Mat3 {
     type Output = Mat3;
 
@@ -183,7 +231,7 @@ pub struct Mat4 {
 
 impl Mat4 {
     pub const IDENTITY: Self =
-        Self::new([1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+        Self::new([1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
 
     pub const fn new(data: [Real; 12]) -> Self {
         Self { data }
@@ -424,3 +472,18 @@ impl MulAssign<Mat4> for Mat4 {
         *self = self.mul_mat4(rhs)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identity_leaves_points_and_directions_alone() {
+        let vector = Vec3::new(1.0, -2.0, 3.0);
+
+        assert_eq!(Mat4::IDENTITY.transform(vector), vector);
+        assert_eq!(Mat4::IDENTITY.transform_direction(vector), vector);
+        assert_eq!(Mat4::IDENTITY.transform_inverse(vector), vector);
+        assert_eq!(Mat3::IDENTITY.transform(vector), vector);
+    }
+}
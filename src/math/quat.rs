@@ -96,6 +96,48 @@ impl Quat {
         (self.squared_magnitude() - 1.0).abs() <= 2e-4
     }
 
+    #[inline(always)]
+    pub fn dot(self, rhs: Self) -> Real {
+        self.r * rhs.r + self.i * rhs.i + self.j * rhs.j + self.k * rhs.k
+    }
+
+    /// Interpolates a couple of orientations along the shortest arc
+    /// between them, at a constant angular speed. Returns this
+    /// orientation when `t` is zero and the other one when it is one.
+    pub fn slerp(self, other: Self, t: Real) -> Self {
+        // Both a quaternion and its negation give the same
+        // orientation, so pick the one on the shorter arc.
+        let mut cos_theta = self.dot(other);
+        let other = if cos_theta < 0.0 {
+            cos_theta = -cos_theta;
+            Self::from_rijk(-other.r, -other.i, -other.j, -other.k)
+        } else {
+            other
+        };
+
+        // For orientations this close the arc is practically a
+        // straight line, and the sine below gets too small to divide
+        // by.
+        let (a, b) = if cos_theta > 0.9995 {
+            (1.0 - t, t)
+        } else {
+            let theta = cos_theta.acos();
+            let sin_theta = theta.sin();
+            (
+                ((1.0 - t) * theta).sin() / sin_theta,
+                (t * theta).sin() / sin_theta,
+            )
+        };
+
+        Self::from_rijk(
+            self.r * a + other.r * b,
+            self.i * a + other.i * b,
+            self.j * a + other.j * b,
+            self.k * a + other.k * b,
+        )
+        .normalized()
+    }
+
     #[inline(always)]
     pub fn magnitude(self) -> Real {
         self.squared_magnitude().sqrt()
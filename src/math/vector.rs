@@ -98,6 +98,13 @@ impl Vec3 {
         }
     }
 
+    /// Interpolates a couple of vectors, returning this vector when
+    /// `t` is zero and the other one when it is one.
+    #[inline(always)]
+    pub fn lerp(self, other: Self, t: Real) -> Self {
+        self + (other - self) * t
+    }
+
     #[inline(always)]
     pub fn component_product(self, rhs: Self) -> Self {
         Self {
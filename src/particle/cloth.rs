@@ -0,0 +1,468 @@
+use crate::{
+    precision::Real,
+    rigid_body::collide_narrow::{Plane, Sphere},
+    Vec3,
+};
+
+use super::{
+    contacts::{ParticleContact, ParticleContactGenerator},
+    fgen::ParticleForceGenerator,
+    Particle, ParticleId, ParticleSet,
+};
+
+/// The springs of a cloth, named after what they resist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClothSpringKind {
+    /// Joins each particle to its neighbours along the grid, keeping
+    /// the cloth from stretching.
+    Structural,
+    /// Joins each particle to its diagonal neighbours, keeping the
+    /// cloth from shearing.
+    Shear,
+    /// Joins each particle to the particles two steps away along the
+    /// grid, keeping the cloth from folding too easily.
+    Bend,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ClothSpring {
+    particle_a: usize,
+    particle_b: usize,
+    rest_length: Real,
+    kind: ClothSpringKind,
+}
+
+/// A rectangular piece of cloth, built from a grid of particles held
+/// together by springs.
+///
+/// The cloth is both a force generator, applying its spring and wind
+/// forces, and a contact generator, colliding its particles with
+/// spheres and planes.
+///
+/// Springs stiff enough to stop cloth from stretching visibly are
+/// unstable at game timesteps, so the springs are kept soft and
+/// [`Cloth::limit_stretch`] is called after each step to pull any
+/// spring stretched past `max_stretch` back in. Call
+/// [`Cloth::update_mesh`] after that to refresh the positions and
+/// normals used for rendering.
+#[derive(Debug, Clone)]
+pub struct Cloth {
+    particles: Vec<ParticleId>,
+    columns: usize,
+    rows: usize,
+    particle_mass: Real,
+    springs: Vec<ClothSpring>,
+
+    /// Holds the spring constants of the springs, relative to the
+    /// mass of the particles, so the cloth behaves the same however
+    /// finely it is divided. Stiffer cloth needs shorter steps.
+    pub structural_stiffness: Real,
+    pub shear_stiffness: Real,
+    pub bend_stiffness: Real,
+    /// Holds the damping of the springs, which opposes the speed at
+    /// which they stretch or compress, relative to the mass of the
+    /// particles.
+    pub damping: Real,
+
+    /// Holds how far, as a fraction of their rest length, the
+    /// structural and shear springs can stretch.
+    pub max_stretch: Real,
+    /// Holds how many times the springs are pulled back in by
+    /// [`Cloth::limit_stretch`].
+    pub stretch_iterations: u32,
+
+    /// Holds the velocity of the air.
+    pub wind: Vec3,
+    /// Holds how strongly the air pushes on the cloth, per unit of
+    /// area and squared speed.
+    pub aerodynamic_coefficient: Real,
+
+    /// Holds the distance particles are kept from colliders.
+    pub thickness: Real,
+    /// Holds the restitution of the contacts with colliders.
+    pub restitution: Real,
+    spheres: Vec<(Vec3, Sphere)>,
+    planes: Vec<Plane>,
+
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+}
+
+impl Cloth {
+    /// Creates the particles of a cloth with `columns` particles
+    /// along `u` and `rows` along `v`, spanning `u` and `v` from
+    /// `origin`, and sharing `mass` between them.
+    pub fn new(
+        particles: &mut ParticleSet,
+        origin: Vec3,
+        u: Vec3,
+        v: Vec3,
+        columns: usize,
+        rows: usize,
+        mass: Real,
+    ) -> Self {
+        assert!(
+            columns > 1 && rows > 1,
+            "A cloth needs at least two particles along each side"
+        );
+
+        let particle_mass = mass / (columns * rows) as Real;
+        let step_u = u / (columns - 1) as Real;
+        let step_v = v / (rows - 1) as Real;
+
+        let mut ids = Vec::with_capacity(columns * rows);
+        for y in 0..rows {
+            for x in 0..columns {
+                let position = origin + step_u * x as Real + step_v * y as Real;
+                ids.push(particles.insert(Particle::new(particle_mass).with_position(position)));
+            }
+        }
+
+        let mut cloth = Self {
+            particles: ids,
+            columns,
+            rows,
+            particle_mass,
+            springs: Vec::new(),
+            structural_stiffness: 50.0,
+            shear_stiffness: 10.0,
+            bend_stiffness: 5.0,
+            damping: 1.0,
+            max_stretch: 0.1,
+            stretch_iterations: 4,
+            wind: Vec3::ZERO,
+            aerodynamic_coefficient: 1.0,
+            thickness: 0.01,
+            restitution: 0.0,
+            spheres: Vec::new(),
+            planes: Vec::new(),
+            positions: Vec::new(),
+            normals: Vec::new(),
+        };
+
+        const SPRINGS: [(isize, isize, ClothSpringKind); 6] = [
+            (1, 0, ClothSpringKind::Structural),
+            (0, 1, ClothSpringKind::Structural),
+            (1, 1, ClothSpringKind::Shear),
+            (1, -1, ClothSpringKind::Shear),
+            (2, 0, ClothSpringKind::Bend),
+            (0, 2, ClothSpringKind::Bend),
+        ];
+
+        for y in 0..rows {
+            for x in 0..columns {
+                for (dx, dy, kind) in SPRINGS {
+                    let (Some(nx), Some(ny)) = (
+                        x.checked_add_signed(dx).filter(|&nx| nx < columns),
+                        y.checked_add_signed(dy).filter(|&ny| ny < rows),
+                    ) else {
+                        continue;
+                    };
+
+                    let (particle_a, particle_b) = (cloth.index(x, y), cloth.index(nx, ny));
+                    let rest_length = particles[cloth.particles[particle_a]]
+                        .position
+                        .distance_to(particles[cloth.particles[particle_b]].position);
+                    cloth.springs.push(ClothSpring {
+                        particle_a,
+                        particle_b,
+                        rest_length,
+                        kind,
+                    });
+                }
+            }
+        }
+
+        cloth.update_mesh(particles);
+        cloth
+    }
+
+    pub fn with_stiffness(mut self, structural: Real, shear: Real, bend: Real) -> Self {
+        self.structural_stiffness = structural;
+        self.shear_stiffness = shear;
+        self.bend_stiffness = bend;
+        self
+    }
+
+    pub fn with_damping(mut self, damping: Real) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    pub fn with_max_stretch(mut self, max_stretch: Real, iterations: u32) -> Self {
+        self.max_stretch = max_stretch;
+        self.stretch_iterations = iterations;
+        self
+    }
+
+    pub fn with_wind(mut self, wind: Vec3, aerodynamic_coefficient: Real) -> Self {
+        self.wind = wind;
+        self.aerodynamic_coefficient = aerodynamic_coefficient;
+        self
+    }
+
+    pub fn with_thickness(mut self, thickness: Real) -> Self {
+        self.thickness = thickness;
+        self
+    }
+
+    pub fn with_restitution(mut self, restitution: Real) -> Self {
+        self.restitution = restitution;
+        self
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the particles of the cloth, row by row.
+    pub fn particles(&self) -> &[ParticleId] {
+        &self.particles
+    }
+
+    /// Returns the index of the particle at the given column and row.
+    pub fn index(&self, x: usize, y: usize) -> usize {
+        x + y * self.columns
+    }
+
+    /// Returns the particle at the given column and row.
+    pub fn particle(&self, x: usize, y: usize) -> ParticleId {
+        self.particles[self.index(x, y)]
+    }
+
+    /// Holds the particle at the given column and row in place.
+    pub fn pin(&self, particles: &mut ParticleSet, x: usize, y: usize) {
+        let particle = &mut particles[self.particle(x, y)];
+        particle.inverse_mass = 0.0;
+        particle.velocity = Vec3::ZERO;
+    }
+
+    /// Lets a pinned particle move again.
+    pub fn unpin(&self, particles: &mut ParticleSet, x: usize, y: usize) {
+        particles[self.particle(x, y)].set_mass(self.particle_mass);
+    }
+
+    /// Adds a sphere, centred on the given point, for the cloth to
+    /// collide with.
+    pub fn add_sphere(&mut self, centre: Vec3, sphere: Sphere) {
+        self.spheres.push((centre, sphere));
+    }
+
+    /// Adds a half-space for the cloth to collide with.
+    pub fn add_plane(&mut self, plane: Plane) {
+        self.planes.push(plane);
+    }
+
+    /// Removes every sphere and plane the cloth collides with.
+    pub fn clear_colliders(&mut self) {
+        self.spheres.clear();
+        self.planes.clear();
+    }
+
+    /// Returns the triangles of the cloth, as indices into its
+    /// particles, positions and normals. Each cell of the grid is
+    /// split into two triangles.
+    pub fn triangles(&self) -> impl Iterator<Item = [usize; 3]> + '_ {
+        (0..self.rows - 1).flat_map(move |y| {
+            (0..self.columns - 1).flat_map(move |x| {
+                let corners = [
+                    self.index(x, y),
+                    self.index(x + 1, y),
+                    self.index(x + 1, y + 1),
+                    self.index(x, y + 1),
+                ];
+                [
+                    [corners[0], corners[1], corners[2]],
+                    [corners[0], corners[2], corners[3]],
+                ]
+            })
+        })
+    }
+
+    /// Copies the positions of the particles and recalculates the
+    /// vertex normals, as the average of the normals of the
+    /// triangles around each vertex.
+    pub fn update_mesh(&mut self, particles: &ParticleSet) {
+        self.positions.clear();
+        self.positions
+            .extend(self.particles.iter().map(|&id| particles[id].position));
+
+        let mut normals = vec![Vec3::ZERO; self.positions.len()];
+        for triangle in self.triangles() {
+            let [a, b, c] = triangle.map(|i| self.positions[i]);
+            // The cross product is weighted by the area of the
+            // triangle, so bigger triangles count for more.
+            let normal = (b - a).cross(c - a);
+            for i in triangle {
+                normals[i] += normal;
+            }
+        }
+
+        self.normals = normals.into_iter().map(Vec3::normalized).collect();
+    }
+
+    /// Returns the positions of the particles as of the last call to
+    /// [`Cloth::update_mesh`].
+    pub fn positions(&self) -> &[Vec3] {
+        &self.positions
+    }
+
+    /// Returns the vertex normals as of the last call to
+    /// [`Cloth::update_mesh`].
+    pub fn normals(&self) -> &[Vec3] {
+        &self.normals
+    }
+
+    /// Pulls the ends of every structural and shear spring stretched
+    /// past `max_stretch` back together, and stops them moving
+    /// further apart. Pinned particles stay where they are.
+    pub fn limit_stretch(&self, particles: &mut ParticleSet) {
+        for _ in 0..self.stretch_iterations {
+            for spring in &self.springs {
+                if spring.kind == ClothSpringKind::Bend {
+                    continue;
+                }
+
+                let [particle_a, particle_b] = particles
+                    .get_disjoint_mut([
+                        self.particles[spring.particle_a],
+                        self.particles[spring.particle_b],
+                    ])
+                    .unwrap();
+
+                let total_inverse_mass = particle_a.inverse_mass + particle_b.inverse_mass;
+                if total_inverse_mass <= 0.0 {
+                    continue;
+                }
+
+                let delta = particle_a.position - particle_b.position;
+                let length = delta.magnitude();
+                let max_length = spring.rest_length * (1.0 + self.max_stretch);
+                if length <= max_length {
+                    continue;
+                }
+
+                // Move the particles back in proportion to their
+                // inverse mass.
+                let direction = delta / length;
+                let move_per_inverse_mass =
+                    direction * ((length - max_length) / total_inverse_mass);
+                particle_a.position -= move_per_inverse_mass * particle_a.inverse_mass;
+                particle_b.position += move_per_inverse_mass * particle_b.inverse_mass;
+
+                // Remove the velocity that is stretching the spring.
+                let separating_velocity =
+                    (particle_a.velocity - particle_b.velocity).dot(direction);
+                if separating_velocity > 0.0 {
+                    let impulse_per_inverse_mass =
+                        direction * (separating_velocity / total_inverse_mass);
+                    particle_a.velocity -= impulse_per_inverse_mass * particle_a.inverse_mass;
+                    particle_b.velocity += impulse_per_inverse_mass * particle_b.inverse_mass;
+                }
+            }
+        }
+    }
+
+    fn stiffness(&self, kind: ClothSpringKind) -> Real {
+        match kind {
+            ClothSpringKind::Structural => self.structural_stiffness,
+            ClothSpringKind::Shear => self.shear_stiffness,
+            ClothSpringKind::Bend => self.bend_stiffness,
+        }
+    }
+
+    fn add_spring_forces(&self, particles: &mut ParticleSet) {
+        for spring in &self.springs {
+            let [particle_a, particle_b] = particles
+                .get_disjoint_mut([
+                    self.particles[spring.particle_a],
+                    self.particles[spring.particle_b],
+                ])
+                .unwrap();
+
+            let delta = particle_a.position - particle_b.position;
+            let direction = delta.normalized();
+            let speed = (particle_a.velocity - particle_b.velocity).dot(direction);
+
+            let force = (-self.stiffness(spring.kind) * (delta.magnitude() - spring.rest_length)
+                - self.damping * speed)
+                * self.particle_mass
+                * direction;
+
+            particle_a.add_force(force);
+            particle_b.add_force(-force);
+        }
+    }
+
+    fn add_wind_forces(&self, particles: &mut ParticleSet) {
+        if self.aerodynamic_coefficient == 0.0 {
+            return;
+        }
+
+        for triangle in self.triangles() {
+            let ids = triangle.map(|i| self.particles[i]);
+            let [a, b, c] = ids.map(|id| &particles[id]);
+
+            // The air pushes on the triangle along its normal, in
+            // proportion to its area and how fast it moves through
+            // the air face on.
+            let cross = (b.position - a.position).cross(c.position - a.position);
+            let area = 0.5 * cross.magnitude();
+            let normal = cross.normalized();
+            let relative_velocity = (a.velocity + b.velocity + c.velocity) / 3.0 - self.wind;
+            let normal_speed = relative_velocity.dot(normal);
+
+            let force = normal
+                * (-self.aerodynamic_coefficient
+                    * area
+                    * normal_speed
+                    * relative_velocity.magnitude());
+
+            for id in ids {
+                particles[id].add_force(force / 3.0);
+            }
+        }
+    }
+}
+
+impl ParticleForceGenerator for Cloth {
+    fn update_forces(&mut self, particles: &mut ParticleSet, _duration: Real) {
+        self.add_spring_forces(particles);
+        self.add_wind_forces(particles);
+    }
+}
+
+impl ParticleContactGenerator for Cloth {
+    fn add_contacts(&self, contacts: &mut [ParticleContact], particles: &ParticleSet) -> usize {
+        let mut used = 0;
+        for &id in &self.particles {
+            let position = particles[id].position;
+
+            let sphere_contacts = self.spheres.iter().filter_map(|(centre, sphere)| {
+                let distance = centre.distance_to(position);
+                let penetration = sphere.radius + self.thickness - distance;
+                (penetration > 0.0).then(|| (centre.direction_to(position), penetration))
+            });
+            let plane_contacts = self.planes.iter().filter_map(|plane| {
+                let penetration = plane.offset + self.thickness - position.dot(plane.normal);
+                (penetration > 0.0).then_some((plane.normal, penetration))
+            });
+
+            for (normal, penetration) in sphere_contacts.chain(plane_contacts) {
+                if used == contacts.len() {
+                    return used;
+                }
+
+                contacts[used] =
+                    ParticleContact::new(id, None, self.restitution, normal, penetration);
+                used += 1;
+            }
+        }
+
+        used
+    }
+}
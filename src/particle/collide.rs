@@ -0,0 +1,477 @@
+use std::collections::HashMap;
+
+use crate::{
+    precision::Real,
+    rigid_body::collide_narrow::{
+        Capsule, Collider, Cuboid, Cylinder, Plane, Primitive, PrimitiveShape, Sphere,
+    },
+    Mat4, Vec3,
+};
+
+use super::{
+    contacts::{ParticleContact, ParticleContactData, ParticleContactGenerator},
+    Particle, ParticleId, ParticleSet, ParticleTargets,
+};
+
+/// A contact generator that collides particles with each other, as
+/// spheres of their radius.
+///
+/// The particles are sorted into a uniform grid first, so only the
+/// particles in neighbouring cells are checked against each other.
+/// Particles with no radius are ignored.
+#[derive(Debug, Clone)]
+pub struct ParticleCollisions {
+    pub targets: ParticleTargets,
+    /// Holds the restitution of the contacts.
+    pub restitution: Real,
+    /// Holds the size of the grid cells. It is never used below the
+    /// diameter of the largest particle, so the default of zero
+    /// picks the smallest cells that work.
+    pub cell_size: Real,
+}
+
+impl ParticleCollisions {
+    pub fn new(targets: impl Into<ParticleTargets>, restitution: Real) -> Self {
+        Self {
+            targets: targets.into(),
+            restitution,
+            cell_size: 0.0,
+        }
+    }
+
+    pub fn with_cell_size(mut self, cell_size: Real) -> Self {
+        self.cell_size = cell_size;
+        self
+    }
+}
+
+impl ParticleContactGenerator for ParticleCollisions {
+    fn add_contacts(&self, contacts: &mut [ParticleContact], particles: &ParticleSet) -> usize {
+        let spheres: Vec<(ParticleId, Vec3, Real)> = self
+            .targets
+            .iter(particles)
+            .filter(|(_, particle)| particle.radius > 0.0)
+            .map(|(id, particle)| (id, particle.position, particle.radius))
+            .collect();
+
+        let max_radius = spheres
+            .iter()
+            .map(|&(_, _, radius)| radius)
+            .fold(0.0, Real::max);
+        if max_radius <= 0.0 {
+            return 0;
+        }
+
+        // With cells at least as wide as the largest particle, a
+        // particle can only touch the ones in its own and the
+        // neighbouring cells.
+        let cell_size = self.cell_size.max(2.0 * max_radius);
+        let mut grid: HashMap<[i32; 3], Vec<usize>> = HashMap::new();
+        for (index, &(_, position, _)) in spheres.iter().enumerate() {
+            grid.entry(grid_cell(position, cell_size))
+                .or_default()
+                .push(index);
+        }
+
+        let mut used = 0;
+        for (index, &(id_a, position_a, radius_a)) in spheres.iter().enumerate() {
+            let [x, y, z] = grid_cell(position_a, cell_size);
+
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        let Some(cell) = grid.get(&[x + dx, y + dy, z + dz]) else {
+                            continue;
+                        };
+
+                        // Every pair is checked once, from the particle
+                        // that comes first.
+                        for &other in cell.iter().filter(|&&other| other > index) {
+                            let (id_b, position_b, radius_b) = spheres[other];
+
+                            let distance = position_a.distance_to(position_b);
+                            let penetration = radius_a + radius_b - distance;
+                            if penetration <= 0.0 {
+                                continue;
+                            }
+
+                            if used == contacts.len() {
+                                return used;
+                            }
+
+                            // Particles in the same place are pushed
+                            // apart vertically.
+                            let normal = if distance > 0.0 {
+                                position_b.direction_to(position_a)
+                            } else {
+                                Vec3::Y
+                            };
+
+                            contacts[used] = ParticleContact {
+                                particle_a: id_a,
+                                particle_b: Some(id_b),
+                                data: ParticleContactData::new(
+                                    self.restitution,
+                                    normal,
+                                    penetration,
+                                ),
+                            };
+                            used += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        used
+    }
+}
+
+/// A contact generator that keeps particles above a horizontal
+/// ground plane.
+#[derive(Debug, Clone)]
+pub struct ParticleGround {
+    pub targets: ParticleTargets,
+    /// Holds the height of the ground.
+    pub height: Real,
+    /// Holds the restitution of the contacts.
+    pub restitution: Real,
+}
+
+impl ParticleGround {
+    pub fn new(targets: impl Into<ParticleTargets>, height: Real, restitution: Real) -> Self {
+        Self {
+            targets: targets.into(),
+            height,
+            restitution,
+        }
+    }
+}
+
+impl ParticleContactGenerator for ParticleGround {
+    fn add_contacts(&self, contacts: &mut [ParticleContact], particles: &ParticleSet) -> usize {
+        let mut used = 0;
+        for (id, particle) in self.targets.iter(particles) {
+            let penetration = self.height - (particle.position.y - particle.radius);
+            if penetration <= 0.0 {
+                continue;
+            }
+
+            if used == contacts.len() {
+                break;
+            }
+
+            contacts[used] = ParticleContact::new(id, None, self.restitution, Vec3::Y, penetration);
+            used += 1;
+        }
+
+        used
+    }
+}
+
+/// A contact generator that keeps particles inside an axis-aligned
+/// box.
+#[derive(Debug, Clone)]
+pub struct ParticleBox {
+    pub targets: ParticleTargets,
+    /// Holds the corner of the box with the lowest coordinates.
+    pub min: Vec3,
+    /// Holds the corner of the box with the highest coordinates.
+    pub max: Vec3,
+    /// Holds the restitution of the contacts.
+    pub restitution: Real,
+}
+
+impl ParticleBox {
+    pub fn new(
+        targets: impl Into<ParticleTargets>,
+        min: Vec3,
+        max: Vec3,
+        restitution: Real,
+    ) -> Self {
+        Self {
+            targets: targets.into(),
+            min,
+            max,
+            restitution,
+        }
+    }
+}
+
+impl ParticleContactGenerator for ParticleBox {
+    fn add_contacts(&self, contacts: &mut [ParticleContact], particles: &ParticleSet) -> usize {
+        let mut used = 0;
+        for (id, particle) in self.targets.iter(particles) {
+            // Check every wall, as a particle in a corner touches
+            // more than one.
+            for axis in 0..3 {
+                let mut normal = Vec3::ZERO;
+
+                let below = self.min[axis] - (particle.position[axis] - particle.radius);
+                if below > 0.0 {
+                    normal[axis] = 1.0;
+                    if used == contacts.len() {
+                        return used;
+                    }
+                    contacts[used] =
+                        ParticleContact::new(id, None, self.restitution, normal, below);
+                    used += 1;
+                }
+
+                let above = particle.position[axis] + particle.radius - self.max[axis];
+                if above > 0.0 {
+                    normal[axis] = -1.0;
+                    if used == contacts.len() {
+                        return used;
+                    }
+                    contacts[used] =
+                        ParticleContact::new(id, None, self.restitution, normal, above);
+                    used += 1;
+                }
+            }
+        }
+
+        used
+    }
+}
+
+/// A contact generator that collides particles with the primitives
+/// of a rigid-body collider.
+///
+/// The collider doesn't move in response to the particles. The
+/// transform has to be kept up to date with the body the collider
+/// belongs to, if any. As with rigid bodies, planes are half-spaces
+/// given in world space and aren't transformed.
+#[derive(Debug, Clone)]
+pub struct ParticleCollider {
+    pub targets: ParticleTargets,
+    pub collider: Collider,
+    /// Holds the transform of the collider in world space.
+    pub transform: Mat4,
+    /// Holds the restitution of the contacts.
+    pub restitution: Real,
+}
+
+impl ParticleCollider {
+    pub fn new(targets: impl Into<ParticleTargets>, collider: Collider, restitution: Real) -> Self {
+        Self {
+            targets: targets.into(),
+            collider,
+            transform: Mat4::IDENTITY,
+            restitution,
+        }
+    }
+
+    pub fn with_transform(mut self, transform: Mat4) -> Self {
+        self.transform = transform;
+        self
+    }
+}
+
+impl ParticleContactGenerator for ParticleCollider {
+    fn add_contacts(&self, contacts: &mut [ParticleContact], particles: &ParticleSet) -> usize {
+        let primitives = match &self.collider {
+            Collider::Single(primitive) => std::slice::from_ref(primitive),
+            Collider::Set(primitives) => primitives.as_slice(),
+        };
+
+        let mut used = 0;
+        for (id, particle) in self.targets.iter(particles) {
+            for primitive in primitives {
+                let Some((normal, penetration)) =
+                    primitive_contact(primitive, self.transform, particle)
+                else {
+                    continue;
+                };
+
+                if used == contacts.len() {
+                    return used;
+                }
+
+                contacts[used] =
+                    ParticleContact::new(id, None, self.restitution, normal, penetration);
+                used += 1;
+            }
+        }
+
+        used
+    }
+}
+
+/// Returns the normal and penetration of the contact between a
+/// particle and a primitive, if they touch.
+fn primitive_contact(
+    primitive: &Primitive,
+    transform: Mat4,
+    particle: &Particle,
+) -> Option<(Vec3, Real)> {
+    let transform = transform.mul_mat4(primitive.offset);
+
+    match primitive.shape {
+        PrimitiveShape::Sphere(sphere) => sphere_contact(sphere, transform, particle),
+        PrimitiveShape::Plane(plane) => half_space_contact(plane, particle),
+        PrimitiveShape::Cuboid(cuboid) => cuboid_contact(cuboid, transform, particle),
+        PrimitiveShape::Capsule(capsule) => capsule_contact(capsule, transform, particle),
+        PrimitiveShape::Cylinder(cylinder) => cylinder_contact(cylinder, transform, particle),
+    }
+}
+
+fn sphere_contact(sphere: Sphere, transform: Mat4, particle: &Particle) -> Option<(Vec3, Real)> {
+    let centre = transform.get_position();
+    let distance = centre.distance_to(particle.position);
+    let penetration = sphere.radius + particle.radius - distance;
+    if penetration <= 0.0 {
+        return None;
+    }
+
+    // A particle right at the centre is pushed out vertically.
+    let normal = if distance > 0.0 {
+        centre.direction_to(particle.position)
+    } else {
+        Vec3::Y
+    };
+
+    Some((normal, penetration))
+}
+
+fn half_space_contact(plane: Plane, particle: &Particle) -> Option<(Vec3, Real)> {
+    let distance = particle.position.dot(plane.normal) - particle.radius - plane.offset;
+    if distance >= 0.0 {
+        return None;
+    }
+
+    Some((plane.normal, -distance))
+}
+
+fn cuboid_contact(cuboid: Cuboid, transform: Mat4, particle: &Particle) -> Option<(Vec3, Real)> {
+    let relative = transform.transform_inverse(particle.position);
+    let closest = relative.clamp(-cuboid.half_size, cuboid.half_size);
+
+    if closest != relative {
+        // The particle is outside the cuboid, so it is pushed away
+        // from the closest point on its surface.
+        let closest_world = transform.transform(closest);
+        let distance = closest_world.distance_to(particle.position);
+        let penetration = particle.radius - distance;
+        if penetration <= 0.0 {
+            return None;
+        }
+
+        return Some((closest_world.direction_to(particle.position), penetration));
+    }
+
+    // The particle is inside the cuboid, so it is pushed out through
+    // the nearest face.
+    let mut axis = 0;
+    let mut depth = Real::MAX;
+    for i in 0..3 {
+        let face_depth = cuboid.half_size[i] - relative[i].abs();
+        if face_depth < depth {
+            depth = face_depth;
+            axis = i;
+        }
+    }
+
+    let mut normal = Vec3::ZERO;
+    normal[axis] = if relative[axis] < 0.0 { -1.0 } else { 1.0 };
+
+    Some((
+        transform.transform_direction(normal),
+        depth + particle.radius,
+    ))
+}
+
+fn capsule_contact(capsule: Capsule, transform: Mat4, particle: &Particle) -> Option<(Vec3, Real)> {
+    // The capsule is a sphere swept along its axis, so the particle
+    // collides with the sphere at the closest point of the axis.
+    let relative = transform.transform_inverse(particle.position);
+    let closest = Vec3::new(
+        0.0,
+        relative.y.clamp(-capsule.half_height, capsule.half_height),
+        0.0,
+    );
+
+    let centre = transform.transform(closest);
+    let distance = centre.distance_to(particle.position);
+    let penetration = capsule.radius + particle.radius - distance;
+    if penetration <= 0.0 {
+        return None;
+    }
+
+    // A particle right on the axis is pushed out vertically.
+    let normal = if distance > 0.0 {
+        centre.direction_to(particle.position)
+    } else {
+        Vec3::Y
+    };
+
+    Some((normal, penetration))
+}
+
+fn cylinder_contact(
+    cylinder: Cylinder,
+    transform: Mat4,
+    particle: &Particle,
+) -> Option<(Vec3, Real)> {
+    let relative = transform.transform_inverse(particle.position);
+    let radial = Vec3::new(relative.x, 0.0, relative.z);
+    let radial_distance = radial.magnitude();
+
+    // A particle right on the axis is pushed out along local X.
+    let radial_direction = if radial_distance > 0.0 {
+        radial / radial_distance
+    } else {
+        Vec3::X
+    };
+
+    let inside_side = radial_distance <= cylinder.radius;
+    let inside_caps = relative.y.abs() <= cylinder.half_height;
+
+    if !(inside_side && inside_caps) {
+        // The particle is outside the cylinder, so it is pushed away
+        // from the closest point on its surface.
+        let closest = radial_direction * radial_distance.min(cylinder.radius)
+            + Vec3::new(
+                0.0,
+                relative
+                    .y
+                    .clamp(-cylinder.half_height, cylinder.half_height),
+                0.0,
+            );
+
+        let closest_world = transform.transform(closest);
+        let distance = closest_world.distance_to(particle.position);
+        let penetration = particle.radius - distance;
+        if penetration <= 0.0 {
+            return None;
+        }
+
+        return Some((closest_world.direction_to(particle.position), penetration));
+    }
+
+    // The particle is inside the cylinder, so it is pushed out
+    // through the side or the cap it is closest to.
+    let side_depth = cylinder.radius - radial_distance;
+    let cap_depth = cylinder.half_height - relative.y.abs();
+
+    let (normal, depth) = if side_depth < cap_depth {
+        (radial_direction, side_depth)
+    } else if relative.y < 0.0 {
+        (Vec3::NEG_Y, cap_depth)
+    } else {
+        (Vec3::Y, cap_depth)
+    };
+
+    Some((
+        transform.transform_direction(normal),
+        depth + particle.radius,
+    ))
+}
+
+pub(crate) fn grid_cell(position: Vec3, cell_size: Real) -> [i32; 3] {
+    [
+        (position.x / cell_size).floor() as i32,
+        (position.y / cell_size).floor() as i32,
+        (position.z / cell_size).floor() as i32,
+    ]
+}
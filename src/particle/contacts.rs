@@ -6,6 +6,23 @@ pub trait ParticleContactGenerator {
     fn add_contacts(&self, contacts: &mut [ParticleContact], particles: &ParticleSet) -> usize;
 }
 
+impl<G: ParticleContactGenerator> ParticleContactGenerator for Vec<G> {
+    /// Adds the contacts of every generator in turn, until there's no
+    /// room left for more.
+    fn add_contacts(&self, contacts: &mut [ParticleContact], particles: &ParticleSet) -> usize {
+        let mut used = 0;
+        for generator in self {
+            if used == contacts.len() {
+                break;
+            }
+
+            used += generator.add_contacts(&mut contacts[used..], particles);
+        }
+
+        used
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct ParticleContact {
     pub particle_a: ParticleId,
@@ -79,48 +96,68 @@ impl ParticleContactResolver {
         self.iterations_used = 0;
 
         while self.iterations_used < self.iterations {
+            // Find the contact with the largest closing velocity, or
+            // the one interpenetrating if none are closing.
             let num_contacts = contacts.len();
             let mut max = Real::MAX;
             let mut max_idx = num_contacts;
 
-            for (i, contact) in contacts.iter_mut().enumerate() {
+            for (i, contact) in contacts.iter().enumerate() {
                 let sep_vel = Self::calculate_seperating_velocity(
                     &particles[contact.particle_a],
                     contact.particle_b.map(|particle| &particles[particle]),
                     contact.data.normal,
                 );
 
-                if sep_vel < max && sep_vel < 0.0 || contact.data.penetration > 0.0 {
+                if sep_vel < max && (sep_vel < 0.0 || contact.data.penetration > 0.0) {
                     max = sep_vel;
                     max_idx = i;
                 }
+            }
 
-                if max_idx == num_contacts {
-                    break;
-                }
+            // Do we have anything worth resolving?
+            if max_idx == num_contacts {
+                break;
+            }
 
-                if let Some(particle_b) = contact.particle_b {
-                    let [particle_a, particle_b] = particles
-                        .get_disjoint_mut([contact.particle_a, particle_b])
-                        .unwrap();
-
-                    Self::resolve_contact(
-                        particle_a,
-                        Some(particle_b),
-                        duration,
-                        &mut contact.data,
-                    );
-                } else {
-                    Self::resolve_contact(
-                        &mut particles[contact.particle_a],
-                        None,
-                        duration,
-                        &mut contact.data,
-                    );
-                }
+            let contact = &mut contacts[max_idx];
+            if let Some(particle_b) = contact.particle_b {
+                let [particle_a, particle_b] = particles
+                    .get_disjoint_mut([contact.particle_a, particle_b])
+                    .unwrap();
+
+                Self::resolve_contact(particle_a, Some(particle_b), duration, &mut contact.data);
+            } else {
+                Self::resolve_contact(
+                    &mut particles[contact.particle_a],
+                    None,
+                    duration,
+                    &mut contact.data,
+                );
+            }
 
-                self.iterations_used += 1;
+            // Update the interpenetrations of every contact involving
+            // the particles that were just moved.
+            let moved = [
+                (Some(contact.particle_a), contact.data.particle_a_movement),
+                (contact.particle_b, contact.data.particle_b_movement),
+            ];
+            for contact in contacts.iter_mut() {
+                for (particle, movement) in moved {
+                    let Some(particle) = particle else {
+                        continue;
+                    };
+
+                    if contact.particle_a == particle {
+                        contact.data.penetration -= movement.dot(contact.data.normal);
+                    }
+                    if contact.particle_b == Some(particle) {
+                        contact.data.penetration += movement.dot(contact.data.normal);
+                    }
+                }
             }
+
+            self.iterations_used += 1;
         }
     }
 
@@ -193,7 +230,7 @@ impl ParticleContactResolver {
         particle_a.velocity += impulse_per_inv_mass * particle_a.inverse_mass;
 
         if let Some(particle_b) = particle_b {
-            particle_b.velocity += impulse_per_inv_mass * particle_b.inverse_mass;
+            particle_b.velocity -= impulse_per_inv_mass * particle_b.inverse_mass;
         }
     }
 
@@ -203,6 +240,10 @@ impl ParticleContactResolver {
         _duration: Real,
         contact_data: &mut ParticleContactData,
     ) {
+        // Nothing moves unless there's interpenetration to resolve.
+        contact_data.particle_a_movement = Vec3::ZERO;
+        contact_data.particle_b_movement = Vec3::ZERO;
+
         if contact_data.penetration <= 0.0 {
             return;
         }
@@ -219,7 +260,7 @@ impl ParticleContactResolver {
         let move_per_inv_mass = contact_data.normal * (contact_data.penetration / total_inv_mass);
         contact_data.particle_a_movement = move_per_inv_mass * particle_a.inverse_mass;
         contact_data.particle_b_movement = match particle_b.as_mut() {
-            Some(particle_b) => move_per_inv_mass * particle_b.inverse_mass,
+            Some(particle_b) => move_per_inv_mass * -particle_b.inverse_mass,
             None => Vec3::ZERO,
         };
 
@@ -242,3 +283,115 @@ impl ParticleContactResolver {
         relative_velocity.dot(contact_normal)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::particle::{
+        collide::ParticleGround,
+        fgen::ParticleForceGeneratorSet,
+        links::{ParticleCable, ParticleRod},
+        ParticlePhysicsSystem,
+    };
+
+    use super::*;
+
+    fn generate(
+        generator: &impl ParticleContactGenerator,
+        particles: &ParticleSet,
+    ) -> Vec<ParticleContact> {
+        let mut contacts = vec![ParticleContact::default(); 4];
+        let used = generator.add_contacts(&mut contacts, particles);
+        contacts.truncate(used);
+        contacts
+    }
+
+    #[test]
+    fn stretched_rod_ends_at_its_length() {
+        let mut particles = ParticleSet::new();
+        let particle_a = particles.insert(Particle::new(1.0));
+        let particle_b =
+            particles.insert(Particle::new(1.0).with_position(Vec3::new(2.0, 0.0, 0.0)));
+        let rod = ParticleRod {
+            particle_a,
+            particle_b,
+            length: 1.0,
+        };
+
+        let mut contacts = generate(&rod, &particles);
+        assert_eq!(contacts.len(), 1);
+
+        ParticleContactResolver::new(2).resolve(&mut contacts, &mut particles, 1.0 / 60.0);
+
+        let length = particles[particle_a]
+            .position
+            .distance_to(particles[particle_b].position);
+        assert!((length - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn slack_cable_makes_no_contact() {
+        let mut particles = ParticleSet::new();
+        let particle_a = particles.insert(Particle::new(1.0));
+        let particle_b =
+            particles.insert(Particle::new(1.0).with_position(Vec3::new(0.5, 0.0, 0.0)));
+        let cable = ParticleCable {
+            particle_a,
+            particle_b,
+            max_length: 1.0,
+            restitution: 0.5,
+        };
+
+        assert!(generate(&cable, &particles).is_empty());
+    }
+
+    #[test]
+    fn particle_resting_on_ground_stays_there() {
+        let mut particles = ParticleSet::new();
+        let particle = particles.insert(
+            Particle::new(1.0)
+                .with_radius(0.5)
+                .with_position(Vec3::new(0.0, 0.5, 0.0))
+                .with_acceleration(Vec3::new(0.0, -9.81, 0.0)),
+        );
+        let ground = ParticleGround::new(vec![particle], 0.0, 0.5);
+
+        let mut system = ParticlePhysicsSystem::new(4, 0);
+        let mut generators = ParticleForceGeneratorSet::new();
+        let energies: Vec<Real> = (0..600)
+            .map(|_| {
+                system.start_frame(&mut particles);
+                system.step_with_contacts(&mut particles, &mut generators, &ground, 1.0 / 60.0);
+
+                // The particle falls into the ground for a frame
+                // before each contact pushes it back out.
+                let resting = &particles[particle];
+                assert!((resting.position.y - 0.5).abs() < 0.01);
+                resting.kinetic_energy() + 9.81 * resting.position.y
+            })
+            .collect();
+
+        let most = |energies: &[Real]| energies.iter().copied().fold(0.0, Real::max);
+        assert!(most(&energies[540..]) <= most(&energies[..60]) + 1e-4);
+    }
+
+    #[test]
+    fn resolving_a_contact_updates_the_others_on_its_particles() {
+        let mut particles = ParticleSet::new();
+        let particle = particles.insert(Particle::new(1.0));
+
+        // Two floors under the same particle, the higher one first.
+        let mut contacts = vec![
+            ParticleContact::new(particle, None, 0.0, Vec3::Y, 0.2),
+            ParticleContact::new(particle, None, 0.0, Vec3::Y, 0.1),
+        ];
+
+        let mut resolver = ParticleContactResolver::new(2);
+        resolver.resolve(&mut contacts, &mut particles, 1.0 / 60.0);
+
+        // Lifting the particle out of the higher floor lifted it out
+        // of the lower one too, so that one needs no resolving.
+        assert!((contacts[1].data.penetration + 0.1).abs() < 1e-6);
+        assert!((particles[particle].position.y - 0.2).abs() < 1e-6);
+        assert_eq!(resolver.iterations_used, 1);
+    }
+}
@@ -2,11 +2,33 @@ use derive_more::{From, Index, IndexMut, IntoIterator};
 use downcast_rs::{impl_downcast, Downcast};
 use slotmap::{new_key_type, SlotMap};
 
-use super::{ParticleId, ParticleSet};
-use crate::{precision::Real, Vec3};
+use super::{ParticleId, ParticleIntegrator, ParticleSet, ParticleTargets};
+use crate::{
+    fgen_common::{drag_force, exceeds_break_force, stiff_spring_target},
+    precision::Real,
+    Vec3,
+};
 
 pub trait ParticleForceGenerator: Downcast {
-    fn update_forces(&self, particles: &mut ParticleSet, duration: Real);
+    fn update_forces(&mut self, particles: &mut ParticleSet, duration: Real);
+
+    /// Broken generators no longer apply forces.
+    fn is_broken(&self) -> bool {
+        false
+    }
+
+    /// Expired generators are removed from their set once they've
+    /// applied their forces.
+    fn is_expired(&self) -> bool {
+        false
+    }
+
+    /// Returns whether the generator works with the given integrator.
+    /// Generators that predict where their particles move during a
+    /// step only work with the integrator they predict for.
+    fn supports_integrator(&self, _integrator: ParticleIntegrator) -> bool {
+        true
+    }
 }
 
 new_key_type! {
@@ -33,10 +55,46 @@ impl ParticleForceGeneratorSet {
         }
     }
 
-    pub fn update_forces(&self, particles: &mut ParticleSet, duration: Real) {
-        for generator in self.inner.values() {
+    /// Applies the forces of every generator that isn't broken, and
+    /// then removes the generators that have expired. Returns the
+    /// generators that broke during this update.
+    pub fn update_forces(
+        &mut self,
+        particles: &mut ParticleSet,
+        duration: Real,
+    ) -> Vec<ForceGeneratorId> {
+        let mut broken = Vec::new();
+        for (id, generator) in self.inner.iter_mut() {
+            if generator.is_broken() {
+                continue;
+            }
+
             generator.update_forces(particles, duration);
+            if generator.is_broken() {
+                broken.push(id);
+            }
         }
+
+        self.inner.retain(|_, generator| !generator.is_expired());
+
+        broken
+    }
+
+    /// Returns whether every generator in the set works with the given
+    /// integrator.
+    pub fn supports_integrator(&self, integrator: ParticleIntegrator) -> bool {
+        self.inner
+            .values()
+            .all(|generator| generator.supports_integrator(integrator))
+    }
+
+    /// Panics if a generator in the set doesn't work with the given
+    /// integrator. This is checked before a step applies any forces.
+    pub(crate) fn assert_supports_integrator(&self, integrator: ParticleIntegrator) {
+        assert!(
+            self.supports_integrator(integrator),
+            "A force generator in the set doesn't work with the {integrator:?} integrator"
+        );
     }
 
     pub fn insert<F: ParticleForceGenerator + 'static>(&mut self, value: F) -> ForceGeneratorId {
@@ -83,27 +141,120 @@ impl ParticleForceGeneratorSet {
         self.inner.reserve(additional)
     }
 }
+
+/// Wraps a force generator so that it only applies its forces for a
+/// limited time, after which it expires and is removed from its set.
+#[derive(Debug, Clone)]
+pub struct ParticleTimed<F> {
+    pub generator: F,
+    /// Holds how long the generator applies its forces for.
+    pub lifetime: Real,
+    time_passed: Real,
+}
+
+impl<F: ParticleForceGenerator> ParticleTimed<F> {
+    pub fn new(generator: F, lifetime: Real) -> Self {
+        Self {
+            generator,
+            lifetime,
+            time_passed: 0.0,
+        }
+    }
+
+    /// Returns how long the generator has been applying its forces.
+    pub fn time_passed(&self) -> Real {
+        self.time_passed
+    }
+}
+
+impl<F: ParticleForceGenerator> ParticleForceGenerator for ParticleTimed<F> {
+    fn update_forces(&mut self, particles: &mut ParticleSet, duration: Real) {
+        self.generator.update_forces(particles, duration);
+        self.time_passed += duration;
+    }
+
+    fn is_broken(&self) -> bool {
+        self.generator.is_broken()
+    }
+
+    fn is_expired(&self) -> bool {
+        self.time_passed >= self.lifetime || self.generator.is_expired()
+    }
+
+    fn supports_integrator(&self, integrator: ParticleIntegrator) -> bool {
+        self.generator.supports_integrator(integrator)
+    }
+}
+
 pub struct ParticleSpring {
     pub target: ParticleId,
     pub other: ParticleId,
     pub spring_constant: Real,
     pub rest_length: Real,
+    /// Holds the damping coefficient, which opposes the speed at
+    /// which the spring stretches or compresses.
+    pub damping: Real,
+    /// Holds the force above which the spring snaps. Unbreakable
+    /// springs have no break force.
+    pub break_force: Option<Real>,
+    broken: bool,
+}
+
+impl ParticleSpring {
+    pub fn new(
+        target: ParticleId,
+        other: ParticleId,
+        spring_constant: Real,
+        rest_length: Real,
+    ) -> Self {
+        Self {
+            target,
+            other,
+            spring_constant,
+            rest_length,
+            damping: 0.0,
+            break_force: None,
+            broken: false,
+        }
+    }
+
+    pub fn with_damping(mut self, damping: Real) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    pub fn with_break_force(mut self, break_force: Real) -> Self {
+        self.break_force = Some(break_force);
+        self
+    }
 }
 
 impl ParticleForceGenerator for ParticleSpring {
-    fn update_forces(&self, particles: &mut ParticleSet, _duration: Real) {
+    fn update_forces(&mut self, particles: &mut ParticleSet, _duration: Real) {
         let [particle, other] = particles
             .get_disjoint_mut([self.target, self.other])
             .unwrap();
 
         let delta = particle.position - other.position;
+        let direction = delta.normalized();
+        let speed = (particle.velocity - other.velocity).dot(direction);
+
+        let force = (-self.spring_constant * (delta.magnitude() - self.rest_length)
+            - self.damping * speed)
+            * direction;
 
-        let force =
-            -self.spring_constant * (delta.magnitude() - self.rest_length) * delta.normalized();
+        if exceeds_break_force(force, self.break_force) {
+            self.broken = true;
+            return;
+        }
 
         particle.add_force(force);
         other.add_force(-force);
     }
+
+    fn is_broken(&self) -> bool {
+        self.broken
+    }
 }
 
 pub struct ParticleAnchoredSpring {
@@ -111,18 +262,130 @@ pub struct ParticleAnchoredSpring {
     pub anchor: Vec3,
     pub spring_constant: Real,
     pub rest_length: Real,
+    /// Holds the damping coefficient, which opposes the speed at
+    /// which the spring stretches or compresses.
+    pub damping: Real,
+    /// Holds the force above which the spring snaps. Unbreakable
+    /// springs have no break force.
+    pub break_force: Option<Real>,
+    broken: bool,
+}
+
+impl ParticleAnchoredSpring {
+    pub fn new(target: ParticleId, anchor: Vec3, spring_constant: Real, rest_length: Real) -> Self {
+        Self {
+            target,
+            anchor,
+            spring_constant,
+            rest_length,
+            damping: 0.0,
+            break_force: None,
+            broken: false,
+        }
+    }
+
+    pub fn with_damping(mut self, damping: Real) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    pub fn with_break_force(mut self, break_force: Real) -> Self {
+        self.break_force = Some(break_force);
+        self
+    }
 }
 
 impl ParticleForceGenerator for ParticleAnchoredSpring {
-    fn update_forces(&self, particles: &mut ParticleSet, _duration: Real) {
+    fn update_forces(&mut self, particles: &mut ParticleSet, _duration: Real) {
         let particle = &mut particles[self.target];
         let delta = particle.position - self.anchor;
+        let direction = delta.normalized();
+        let speed = particle.velocity.dot(direction);
 
-        let force =
-            -self.spring_constant * (delta.magnitude() - self.rest_length) * delta.normalized();
+        let force = (-self.spring_constant * (delta.magnitude() - self.rest_length)
+            - self.damping * speed)
+            * direction;
+
+        if exceeds_break_force(force, self.break_force) {
+            self.broken = true;
+            return;
+        }
 
         particle.add_force(force);
     }
+
+    fn is_broken(&self) -> bool {
+        self.broken
+    }
+}
+
+/// A force generator that fakes a stiff spring force, and where
+/// one end is attached to a fixed point in space.
+///
+/// Rather than applying Hooke's law, it works out where the damped
+/// harmonic motion of the particle takes it by the end of the frame
+/// and applies the force that gets it there, so it stays stable with
+/// spring constants that would make an ordinary spring explode. The
+/// spring has no rest length, and it assumes the particle isn't
+/// affected by any other force.
+///
+/// The force is worked out for the way
+/// [`ParticleIntegrator::ExplicitEuler`] moves the particle, so the
+/// spring only works with that integrator.
+pub struct ParticleStiffSpring {
+    pub target: ParticleId,
+    pub anchor: Vec3,
+    /// Holds the spring constant, relative to the mass of the
+    /// particle.
+    pub spring_constant: Real,
+    /// Holds the damping on the oscillation of the spring. It has
+    /// to be below `2 * spring_constant.sqrt()`, as the fake spring
+    /// only handles underdamped motion.
+    pub damping: Real,
+}
+
+impl ParticleStiffSpring {
+    pub fn new(target: ParticleId, anchor: Vec3, spring_constant: Real, damping: Real) -> Self {
+        Self {
+            target,
+            anchor,
+            spring_constant,
+            damping,
+        }
+    }
+}
+
+impl ParticleForceGenerator for ParticleStiffSpring {
+    fn update_forces(&mut self, particles: &mut ParticleSet, duration: Real) {
+        let particle = &mut particles[self.target];
+
+        // Check that we do not have infinite mass.
+        if particle.inverse_mass == 0.0 {
+            return;
+        }
+
+        let offset = particle.position - self.anchor;
+        let Some(target) = stiff_spring_target(
+            offset,
+            particle.velocity,
+            self.spring_constant,
+            self.damping,
+            duration,
+        ) else {
+            return;
+        };
+
+        // Particles move with their old velocity before the force is
+        // applied, so the force sets up the velocity that reaches the
+        // target over the next frame.
+        let acceleration = (target - offset) * (duration * duration).recip()
+            - particle.velocity * (2.0 / duration);
+        particle.add_force(acceleration * particle.mass());
+    }
+
+    fn supports_integrator(&self, integrator: ParticleIntegrator) -> bool {
+        integrator == ParticleIntegrator::ExplicitEuler
+    }
 }
 
 pub struct ParticleBungee {
@@ -133,7 +396,7 @@ pub struct ParticleBungee {
 }
 
 impl ParticleForceGenerator for ParticleBungee {
-    fn update_forces(&self, particles: &mut ParticleSet, _duration: Real) {
+    fn update_forces(&mut self, particles: &mut ParticleSet, _duration: Real) {
         let [particle, other] = particles
             .get_disjoint_mut([self.target, self.other])
             .unwrap();
@@ -180,7 +443,7 @@ impl ParticleBuoyancy {
 }
 
 impl ParticleForceGenerator for ParticleBuoyancy {
-    fn update_forces(&self, particles: &mut ParticleSet, _duration: Real) {
+    fn update_forces(&mut self, particles: &mut ParticleSet, _duration: Real) {
         let particle = &mut particles[self.target];
         let depth = particle.position.y;
 
@@ -199,3 +462,151 @@ impl ParticleForceGenerator for ParticleBuoyancy {
         particle.add_force(Vec3::Y * buoyancy_force);
     }
 }
+
+/// A force generator that applies a uniform gravitational force to
+/// a set of particles.
+#[derive(Debug, Clone)]
+pub struct ParticleGravity {
+    pub targets: ParticleTargets,
+    /// Holds the acceleration due to gravity.
+    pub gravity: Vec3,
+}
+
+impl ParticleGravity {
+    pub fn new(targets: impl Into<ParticleTargets>, gravity: Vec3) -> Self {
+        Self {
+            targets: targets.into(),
+            gravity,
+        }
+    }
+}
+
+impl ParticleForceGenerator for ParticleGravity {
+    fn update_forces(&mut self, particles: &mut ParticleSet, _duration: Real) {
+        self.targets.for_each_mut(particles, |_, particle| {
+            // Check that we do not have infinite mass.
+            if particle.inverse_mass == 0.0 {
+                return;
+            }
+
+            particle.add_force(self.gravity * particle.mass());
+        });
+    }
+}
+
+/// A force generator that pulls a set of particles towards a point,
+/// with a force that falls off with the square of the distance.
+#[derive(Debug, Clone)]
+pub struct ParticleGravityWell {
+    pub targets: ParticleTargets,
+    /// Holds the position of the centre of the well.
+    pub centre: Vec3,
+    /// Holds the strength of the well: the acceleration it gives a
+    /// particle one unit away from its centre.
+    pub strength: Real,
+    /// Holds the distance below which the force stops growing, so
+    /// particles passing close to the centre aren't flung away.
+    pub min_distance: Real,
+}
+
+impl ParticleGravityWell {
+    pub fn new(targets: impl Into<ParticleTargets>, centre: Vec3, strength: Real) -> Self {
+        Self {
+            targets: targets.into(),
+            centre,
+            strength,
+            min_distance: 0.1,
+        }
+    }
+
+    pub fn with_min_distance(mut self, min_distance: Real) -> Self {
+        self.min_distance = min_distance;
+        self
+    }
+}
+
+impl ParticleForceGenerator for ParticleGravityWell {
+    fn update_forces(&mut self, particles: &mut ParticleSet, _duration: Real) {
+        self.targets.for_each_mut(particles, |_, particle| {
+            if particle.inverse_mass == 0.0 {
+                return;
+            }
+
+            let offset = self.centre - particle.position;
+            let squared_distance = offset
+                .squared_magnitude()
+                .max(self.min_distance * self.min_distance);
+
+            let acceleration = self.strength / squared_distance;
+            particle.add_force(offset.normalized() * acceleration * particle.mass());
+        });
+    }
+}
+
+/// A force generator that applies a drag force to a set of
+/// particles. The magnitude of the force is `k1 * v + k2 * v^2`,
+/// where `v` is the speed of the particle.
+#[derive(Debug, Clone)]
+pub struct ParticleDrag {
+    pub targets: ParticleTargets,
+    /// Holds the velocity drag coefficient.
+    pub k1: Real,
+    /// Holds the velocity squared drag coefficient.
+    pub k2: Real,
+}
+
+impl ParticleDrag {
+    pub fn new(targets: impl Into<ParticleTargets>, k1: Real, k2: Real) -> Self {
+        Self {
+            targets: targets.into(),
+            k1,
+            k2,
+        }
+    }
+}
+
+impl ParticleForceGenerator for ParticleDrag {
+    fn update_forces(&mut self, particles: &mut ParticleSet, _duration: Real) {
+        self.targets.for_each_mut(particles, |_, particle| {
+            particle.add_force(drag_force(particle.velocity, self.k1, self.k2));
+        });
+    }
+}
+
+/// A force generator that applies drag against moving air to a set
+/// of particles, so they get carried along by the wind.
+#[derive(Debug, Clone)]
+pub struct ParticleWind {
+    pub targets: ParticleTargets,
+    /// Holds the velocity of the air.
+    pub wind_velocity: Vec3,
+    /// Holds the velocity drag coefficient.
+    pub k1: Real,
+    /// Holds the velocity squared drag coefficient.
+    pub k2: Real,
+}
+
+impl ParticleWind {
+    pub fn new(
+        targets: impl Into<ParticleTargets>,
+        wind_velocity: Vec3,
+        k1: Real,
+        k2: Real,
+    ) -> Self {
+        Self {
+            targets: targets.into(),
+            wind_velocity,
+            k1,
+            k2,
+        }
+    }
+}
+
+impl ParticleForceGenerator for ParticleWind {
+    fn update_forces(&mut self, particles: &mut ParticleSet, _duration: Real) {
+        self.targets.for_each_mut(particles, |_, particle| {
+            let relative_velocity = particle.velocity - self.wind_velocity;
+            particle.add_force(drag_force(relative_velocity, self.k1, self.k2));
+        });
+    }
+}
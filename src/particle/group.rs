@@ -0,0 +1,106 @@
+use std::ops::{BitOr, BitOrAssign};
+
+use super::{Particle, ParticleId, ParticleSet};
+
+/// The groups a particle belongs to, stored as a bit mask so a
+/// particle can be tagged with up to 32 groups at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ParticleGroup(pub u32);
+
+impl ParticleGroup {
+    /// Holds no group at all.
+    pub const NONE: Self = Self(0);
+    /// Holds every group.
+    pub const ALL: Self = Self(u32::MAX);
+
+    /// Returns the group with the given index, which has to be
+    /// below 32.
+    pub const fn new(index: u32) -> Self {
+        assert!(index < u32::BITS, "There are only 32 particle groups");
+        Self(1 << index)
+    }
+
+    /// Returns whether every group in `other` is also in this one.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns whether this and `other` have any group in common.
+    pub const fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl BitOr for ParticleGroup {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for ParticleGroup {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// The particles a generator acts on: either a fixed list of ids, or
+/// every particle in one of a set of groups.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParticleTargets {
+    Ids(Vec<ParticleId>),
+    Group(ParticleGroup),
+}
+
+impl ParticleTargets {
+    /// Iterates over the targeted particles. Ids that are no longer
+    /// in the set are skipped.
+    pub fn iter<'a>(
+        &'a self,
+        particles: &'a ParticleSet,
+    ) -> Box<dyn Iterator<Item = (ParticleId, &'a Particle)> + 'a> {
+        match self {
+            Self::Ids(ids) => Box::new(
+                ids.iter()
+                    .filter_map(|&id| particles.get(id).map(|particle| (id, particle))),
+            ),
+            Self::Group(group) => Box::new(particles.group(*group)),
+        }
+    }
+
+    /// Calls `f` with every targeted particle. Ids that are no longer
+    /// in the set are skipped.
+    pub fn for_each_mut(
+        &self,
+        particles: &mut ParticleSet,
+        mut f: impl FnMut(ParticleId, &mut Particle),
+    ) {
+        match self {
+            Self::Ids(ids) => {
+                for &id in ids {
+                    if let Some(particle) = particles.get_mut(id) {
+                        f(id, particle);
+                    }
+                }
+            }
+            Self::Group(group) => {
+                for (id, particle) in particles.group_mut(*group) {
+                    f(id, particle);
+                }
+            }
+        }
+    }
+}
+
+impl From<Vec<ParticleId>> for ParticleTargets {
+    fn from(ids: Vec<ParticleId>) -> Self {
+        Self::Ids(ids)
+    }
+}
+
+impl From<ParticleGroup> for ParticleTargets {
+    fn from(group: ParticleGroup) -> Self {
+        Self::Group(group)
+    }
+}
@@ -0,0 +1,250 @@
+use crate::{precision::Real, Vec3};
+
+use super::{
+    fgen::{ForceGeneratorId, ParticleForceGeneratorSet},
+    Particle, ParticleId, ParticleSet,
+};
+
+/// The methods a [`ParticlePhysicsSystem`](super::ParticlePhysicsSystem)
+/// can use to move its particles forward in time.
+///
+/// The methods that evaluate the forces more than once per step are
+/// more accurate, but cost that many times more. Every evaluation
+/// runs all the force generators, with the duration of the step split
+/// between the evaluations, so generators that keep time still see
+/// the whole step pass once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParticleIntegrator {
+    /// Moves the particles with their velocity, and then updates the
+    /// velocity. This is the cheapest method, but it gains energy,
+    /// so it relies on damping to stay stable.
+    #[default]
+    ExplicitEuler,
+    /// Updates the velocity, and then moves the particles with the
+    /// new velocity. It costs the same as explicit Euler, but keeps
+    /// the energy of springs and orbits from growing.
+    SemiImplicitEuler,
+    /// Moves the particles with their acceleration at the start of
+    /// the step, and updates their velocity with the average of the
+    /// accelerations at the start and end. The forces are evaluated
+    /// twice per step.
+    VelocityVerlet,
+    /// The classic fourth order Runge-Kutta method. The forces are
+    /// evaluated four times per step.
+    RungeKutta4,
+}
+
+impl ParticleIntegrator {
+    /// Applies the force generators and integrates the particles
+    /// forward by the given duration. Returns the generators that
+    /// broke along the way.
+    pub(crate) fn integrate(
+        self,
+        particles: &mut ParticleSet,
+        generators: &mut ParticleForceGeneratorSet,
+        duration: Real,
+    ) -> Vec<ForceGeneratorId> {
+        match self {
+            Self::ExplicitEuler => {
+                let broken = generators.update_forces(particles, duration);
+                for particle in particles.particles_mut() {
+                    particle.integrate(duration);
+                }
+                broken
+            }
+            Self::SemiImplicitEuler => {
+                let broken = generators.update_forces(particles, duration);
+                for particle in particles.particles_mut() {
+                    integrate_semi_implicit(particle, duration);
+                }
+                broken
+            }
+            Self::VelocityVerlet => velocity_verlet(particles, generators, duration),
+            Self::RungeKutta4 => runge_kutta_4(particles, generators, duration),
+        }
+    }
+}
+
+fn integrate_semi_implicit(particle: &mut Particle, duration: Real) {
+    if particle.inverse_mass <= 0.0 {
+        return;
+    }
+
+    let resulting_acc = particle.acceleration + particle.force_accum * particle.inverse_mass;
+
+    particle.velocity =
+        particle.velocity * particle.damping.powf(duration) + resulting_acc * duration;
+    particle.position += particle.velocity * duration;
+
+    particle.clear_accumulator();
+}
+
+/// The state of a particle at the start of a step.
+struct Initial {
+    id: ParticleId,
+    position: Vec3,
+    velocity: Vec3,
+    /// Holds the forces added to the particle before the step, which
+    /// are kept for every evaluation.
+    force: Vec3,
+}
+
+fn initial_states(particles: &ParticleSet) -> Vec<Initial> {
+    particles
+        .iter()
+        .filter(|(_, particle)| particle.inverse_mass > 0.0)
+        .map(|(id, particle)| Initial {
+            id,
+            position: particle.position,
+            velocity: particle.velocity,
+            force: particle.force_accum,
+        })
+        .collect()
+}
+
+/// Runs the force generators with the particles where they are, and
+/// returns the acceleration of every particle in the initial states.
+fn evaluate(
+    initial: &[Initial],
+    particles: &mut ParticleSet,
+    generators: &mut ParticleForceGeneratorSet,
+    duration: Real,
+    broken: &mut Vec<ForceGeneratorId>,
+) -> Vec<Vec3> {
+    for state in initial {
+        particles[state.id].force_accum = state.force;
+    }
+
+    broken.extend(generators.update_forces(particles, duration));
+
+    initial
+        .iter()
+        .map(|state| {
+            let particle = &mut particles[state.id];
+            let acceleration = particle.acceleration + particle.force_accum * particle.inverse_mass;
+            particle.clear_accumulator();
+            acceleration
+        })
+        .collect()
+}
+
+fn velocity_verlet(
+    particles: &mut ParticleSet,
+    generators: &mut ParticleForceGeneratorSet,
+    duration: Real,
+) -> Vec<ForceGeneratorId> {
+    let initial = initial_states(particles);
+    let mut broken = Vec::new();
+
+    let start = evaluate(&initial, particles, generators, duration / 2.0, &mut broken);
+    for (state, acceleration) in initial.iter().zip(&start) {
+        particles[state.id].position = state.position
+            + state.velocity * duration
+            + *acceleration * (duration * duration / 2.0);
+    }
+
+    let end = evaluate(&initial, particles, generators, duration / 2.0, &mut broken);
+    for ((state, start), end) in initial.iter().zip(&start).zip(&end) {
+        let particle = &mut particles[state.id];
+        particle.velocity =
+            state.velocity * particle.damping.powf(duration) + (*start + *end) * (duration / 2.0);
+    }
+
+    broken
+}
+
+fn runge_kutta_4(
+    particles: &mut ParticleSet,
+    generators: &mut ParticleForceGeneratorSet,
+    duration: Real,
+) -> Vec<ForceGeneratorId> {
+    let initial = initial_states(particles);
+    let mut broken = Vec::new();
+
+    // Each stage samples the particles part of the way through the
+    // step, from the velocities and accelerations of the one before.
+    let mut velocities: Vec<Vec<Vec3>> = Vec::with_capacity(4);
+    let mut accelerations: Vec<Vec<Vec3>> = Vec::with_capacity(4);
+    for (stage, weight) in [1.0 / 6.0, 1.0 / 3.0, 1.0 / 3.0, 1.0 / 6.0]
+        .into_iter()
+        .enumerate()
+    {
+        if let (Some(velocity), Some(acceleration)) = (velocities.last(), accelerations.last()) {
+            let fraction = if stage == 3 { 1.0 } else { 0.5 };
+            for ((state, velocity), acceleration) in initial.iter().zip(velocity).zip(acceleration)
+            {
+                let particle = &mut particles[state.id];
+                particle.position = state.position + *velocity * (duration * fraction);
+                particle.velocity = state.velocity + *acceleration * (duration * fraction);
+            }
+        }
+
+        velocities.push(
+            initial
+                .iter()
+                .map(|state| particles[state.id].velocity)
+                .collect(),
+        );
+        accelerations.push(evaluate(
+            &initial,
+            particles,
+            generators,
+            duration * weight,
+            &mut broken,
+        ));
+    }
+
+    for (index, state) in initial.iter().enumerate() {
+        let velocity = (velocities[0][index]
+            + (velocities[1][index] + velocities[2][index]) * 2.0
+            + velocities[3][index])
+            / 6.0;
+        let acceleration = (accelerations[0][index]
+            + (accelerations[1][index] + accelerations[2][index]) * 2.0
+            + accelerations[3][index])
+            / 6.0;
+
+        let particle = &mut particles[state.id];
+        particle.position = state.position + velocity * duration;
+        particle.velocity =
+            state.velocity * particle.damping.powf(duration) + acceleration * duration;
+    }
+
+    broken
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::particle::{
+        fgen::ParticleStiffSpring, pbd::ParticlePbdSolver, ParticlePhysicsSystem,
+    };
+
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "doesn't work with the SemiImplicitEuler integrator")]
+    fn stiff_spring_needs_explicit_euler() {
+        let mut particles = ParticleSet::new();
+        let particle = particles.insert(Particle::new(1.0).with_position(Vec3::X));
+
+        let mut generators = ParticleForceGeneratorSet::new();
+        generators.insert(ParticleStiffSpring::new(particle, Vec3::ZERO, 100.0, 1.0));
+
+        let mut system =
+            ParticlePhysicsSystem::new(1, 1).with_integrator(ParticleIntegrator::SemiImplicitEuler);
+        system.start_frame(&mut particles);
+        system.step(&mut particles, &mut generators, 1.0 / 60.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't work with the SemiImplicitEuler integrator")]
+    fn position_based_solver_checks_its_generators() {
+        let mut particles = ParticleSet::new();
+        let particle = particles.insert(Particle::new(1.0).with_position(Vec3::X));
+
+        let mut generators = ParticleForceGeneratorSet::new();
+        generators.insert(ParticleStiffSpring::new(particle, Vec3::ZERO, 100.0, 1.0));
+
+        ParticlePbdSolver::new(4).step(&mut particles, &mut generators, &[], 1.0 / 60.0);
+    }
+}
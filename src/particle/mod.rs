@@ -1,8 +1,20 @@
+pub mod cloth;
+pub mod collide;
 pub mod contacts;
 pub mod fgen;
+mod group;
+mod integrator;
 pub mod links;
+pub mod pbd;
+pub mod soft_body;
+pub mod sph;
+mod stepper;
+pub mod structures;
 mod system;
 
+pub use group::{ParticleGroup, ParticleTargets};
+pub use integrator::ParticleIntegrator;
+pub use stepper::ParticleFixedStepper;
 pub use system::ParticlePhysicsSystem;
 
 use crate::{precision::Real, Vec3};
@@ -18,6 +30,12 @@ pub struct Particle {
     pub acceleration: Vec3,
     pub damping: Real,
     pub inverse_mass: Real,
+    /// Holds the radius of the particle, used when it collides with
+    /// other particles. Particles with no radius don't collide.
+    pub radius: Real,
+    /// Holds the groups the particle belongs to, so generators can
+    /// act on all of them at once.
+    pub groups: ParticleGroup,
     force_accum: Vec3,
 }
 
@@ -36,6 +54,8 @@ impl Particle {
             acceleration: Vec3::ZERO,
             damping: 0.99,
             inverse_mass,
+            radius: 0.0,
+            groups: ParticleGroup::NONE,
             force_accum: Vec3::ZERO,
         }
     }
@@ -61,6 +81,17 @@ impl Particle {
         self
     }
 
+    pub fn with_radius(mut self, radius: Real) -> Self {
+        assert!(radius >= 0.0, "Particles can't have a negative radius");
+        self.radius = radius;
+        self
+    }
+
+    pub fn with_groups(mut self, groups: ParticleGroup) -> Self {
+        self.groups = groups;
+        self
+    }
+
     pub fn with_mass(mut self, mass: Real) -> Self {
         self.set_mass(mass);
         self
@@ -201,6 +232,23 @@ impl ParticleSet {
         self.inner.iter_mut()
     }
 
+    /// Iterates over the particles in any of the given groups.
+    pub fn group(&self, group: ParticleGroup) -> impl Iterator<Item = (ParticleId, &Particle)> {
+        self.inner
+            .iter()
+            .filter(move |(_, particle)| particle.groups.intersects(group))
+    }
+
+    /// Mutably iterates over the particles in any of the given groups.
+    pub fn group_mut(
+        &mut self,
+        group: ParticleGroup,
+    ) -> impl Iterator<Item = (ParticleId, &mut Particle)> {
+        self.inner
+            .iter_mut()
+            .filter(move |(_, particle)| particle.groups.intersects(group))
+    }
+
     pub fn drain(&mut self) -> impl Iterator<Item = (ParticleId, Particle)> + '_ {
         self.inner.drain()
     }
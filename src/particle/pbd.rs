@@ -0,0 +1,264 @@
+use slotmap::SecondaryMap;
+
+use crate::{precision::Real, Vec3};
+
+use super::{
+    fgen::{ForceGeneratorId, ParticleForceGeneratorSet},
+    links::{ParticleCable, ParticleCableConstraint, ParticleRod, ParticleRodConstraint},
+    ParticleId, ParticleIntegrator, ParticleSet,
+};
+
+/// What the far end of a distance constraint is attached to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DistanceTarget {
+    Particle(ParticleId),
+    Anchor(Vec3),
+}
+
+/// Whether a distance constraint holds its particles at its length,
+/// like a rod, or only stops them moving further apart, like a
+/// cable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DistanceKind {
+    Rod,
+    Cable,
+}
+
+/// Keeps a particle at a distance from another particle or from a
+/// fixed point in the world.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DistanceConstraint {
+    pub particle: ParticleId,
+    pub target: DistanceTarget,
+    pub length: Real,
+    pub kind: DistanceKind,
+    /// Holds the inverse stiffness of the constraint. Zero makes it
+    /// rigid, larger values let it stretch like a spring.
+    pub compliance: Real,
+}
+
+impl DistanceConstraint {
+    pub fn rod(particle_a: ParticleId, particle_b: ParticleId, length: Real) -> Self {
+        Self::new(
+            particle_a,
+            DistanceTarget::Particle(particle_b),
+            length,
+            DistanceKind::Rod,
+        )
+    }
+
+    pub fn cable(particle_a: ParticleId, particle_b: ParticleId, max_length: Real) -> Self {
+        Self::new(
+            particle_a,
+            DistanceTarget::Particle(particle_b),
+            max_length,
+            DistanceKind::Cable,
+        )
+    }
+
+    pub fn anchored_rod(particle: ParticleId, anchor: Vec3, length: Real) -> Self {
+        Self::new(
+            particle,
+            DistanceTarget::Anchor(anchor),
+            length,
+            DistanceKind::Rod,
+        )
+    }
+
+    pub fn anchored_cable(particle: ParticleId, anchor: Vec3, max_length: Real) -> Self {
+        Self::new(
+            particle,
+            DistanceTarget::Anchor(anchor),
+            max_length,
+            DistanceKind::Cable,
+        )
+    }
+
+    fn new(particle: ParticleId, target: DistanceTarget, length: Real, kind: DistanceKind) -> Self {
+        Self {
+            particle,
+            target,
+            length,
+            kind,
+            compliance: 0.0,
+        }
+    }
+
+    pub fn with_compliance(mut self, compliance: Real) -> Self {
+        self.compliance = compliance;
+        self
+    }
+}
+
+impl From<ParticleRod> for DistanceConstraint {
+    fn from(rod: ParticleRod) -> Self {
+        Self::rod(rod.particle_a, rod.particle_b, rod.length)
+    }
+}
+
+/// The restitution of the cable is ignored, as the solver doesn't
+/// bounce.
+impl From<ParticleCable> for DistanceConstraint {
+    fn from(cable: ParticleCable) -> Self {
+        Self::cable(cable.particle_a, cable.particle_b, cable.max_length)
+    }
+}
+
+impl From<ParticleRodConstraint> for DistanceConstraint {
+    fn from(rod: ParticleRodConstraint) -> Self {
+        Self::anchored_rod(rod.particle_a, rod.anchor, rod.length)
+    }
+}
+
+/// The restitution of the cable is ignored, as the solver doesn't
+/// bounce.
+impl From<ParticleCableConstraint> for DistanceConstraint {
+    fn from(cable: ParticleCableConstraint) -> Self {
+        Self::anchored_cable(cable.particle_a, cable.anchor, cable.max_length)
+    }
+}
+
+/// An alternative to [`ParticlePhysicsSystem`](super::ParticlePhysicsSystem)
+/// that moves particles with extended position-based dynamics.
+///
+/// Rather than resolving contacts with impulses, the particles are
+/// moved to where their velocity takes them, and then projected back
+/// onto their distance constraints a number of times. Their velocity
+/// is then worked out from how far they moved. This keeps stiff
+/// ropes and chains stable however many links they have.
+#[derive(Debug, Clone)]
+pub struct ParticlePbdSolver {
+    pub iterations: u32,
+    previous_positions: SecondaryMap<ParticleId, Vec3>,
+    lambdas: Vec<Real>,
+    broken_generators: Vec<ForceGeneratorId>,
+}
+
+impl ParticlePbdSolver {
+    pub fn new(iterations: u32) -> Self {
+        Self {
+            iterations,
+            previous_positions: SecondaryMap::new(),
+            lambdas: Vec::new(),
+            broken_generators: Vec::new(),
+        }
+    }
+
+    /// Applies the forces of the generators, moves the particles,
+    /// and projects them onto the constraints.
+    pub fn step(
+        &mut self,
+        particles: &mut ParticleSet,
+        generators: &mut ParticleForceGeneratorSet,
+        constraints: &[DistanceConstraint],
+        duration: Real,
+    ) {
+        // The particles are moved with semi-implicit Euler, so the
+        // generators have to work with it.
+        generators.assert_supports_integrator(ParticleIntegrator::SemiImplicitEuler);
+
+        if duration <= 0.0 {
+            return;
+        }
+
+        self.broken_generators = generators.update_forces(particles, duration);
+
+        // Predict where the particles move to without the
+        // constraints.
+        self.previous_positions.clear();
+        for (id, particle) in particles.iter_mut() {
+            self.previous_positions.insert(id, particle.position);
+            if particle.inverse_mass <= 0.0 {
+                particle.clear_accumulator();
+                continue;
+            }
+
+            let acceleration = particle.acceleration + particle.force_accum * particle.inverse_mass;
+            particle.velocity =
+                particle.velocity * particle.damping.powf(duration) + acceleration * duration;
+            particle.position += particle.velocity * duration;
+            particle.clear_accumulator();
+        }
+
+        // Project the particles onto the constraints. The compliance
+        // is scaled by the step, so it doesn't depend on the
+        // framerate.
+        self.lambdas.clear();
+        self.lambdas.resize(constraints.len(), 0.0);
+        let inverse_duration_squared = (duration * duration).recip();
+        for _ in 0..self.iterations {
+            for (constraint, lambda) in constraints.iter().zip(&mut self.lambdas) {
+                project(
+                    constraint,
+                    lambda,
+                    constraint.compliance * inverse_duration_squared,
+                    particles,
+                );
+            }
+        }
+
+        // Work out the velocities from how far the particles moved.
+        for (id, particle) in particles.iter_mut() {
+            if particle.inverse_mass <= 0.0 {
+                continue;
+            }
+
+            if let Some(&previous) = self.previous_positions.get(id) {
+                particle.velocity = (particle.position - previous) / duration;
+            }
+        }
+    }
+
+    /// Returns the force generators that broke during the last step.
+    pub fn broken_generators(&self) -> &[ForceGeneratorId] {
+        &self.broken_generators
+    }
+}
+
+fn project(
+    constraint: &DistanceConstraint,
+    lambda: &mut Real,
+    alpha: Real,
+    particles: &mut ParticleSet,
+) {
+    let (position_a, inverse_mass_a) = {
+        let particle = &particles[constraint.particle];
+        (particle.position, particle.inverse_mass)
+    };
+    let (position_b, inverse_mass_b) = match constraint.target {
+        DistanceTarget::Particle(id) => {
+            let particle = &particles[id];
+            (particle.position, particle.inverse_mass)
+        }
+        DistanceTarget::Anchor(anchor) => (anchor, 0.0),
+    };
+
+    let total_inverse_mass = inverse_mass_a + inverse_mass_b;
+    if total_inverse_mass <= 0.0 {
+        return;
+    }
+
+    let delta = position_a - position_b;
+    let distance = delta.magnitude();
+    if distance <= 0.0 {
+        return;
+    }
+
+    let error = distance - constraint.length;
+    if constraint.kind == DistanceKind::Cable && error <= 0.0 {
+        return;
+    }
+
+    let mut delta_lambda = (-error - alpha * *lambda) / (total_inverse_mass + alpha);
+    if constraint.kind == DistanceKind::Cable {
+        // A cable can only pull its ends together.
+        delta_lambda = (*lambda + delta_lambda).min(0.0) - *lambda;
+    }
+    *lambda += delta_lambda;
+
+    let correction = delta * (delta_lambda / distance);
+    particles[constraint.particle].position += correction * inverse_mass_a;
+    if let DistanceTarget::Particle(id) = constraint.target {
+        particles[id].position -= correction * inverse_mass_b;
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::BTreeSet;
+
+use crate::{precision::Real, Vec3};
+
+use super::{fgen::ParticleForceGenerator, Particle, ParticleId, ParticleSet};
+
+#[derive(Debug, Clone, Copy)]
+struct SoftBodySpring {
+    particle_a: usize,
+    particle_b: usize,
+    rest_length: Real,
+}
+
+/// A soft body built from a closed triangle mesh: a particle at
+/// every vertex, a spring along every edge, and a pressure force
+/// that pushes the faces outwards when the body is squashed, and
+/// pulls them in when it is stretched, so it keeps its volume.
+///
+/// The soft body is a force generator. Its particles can be
+/// collided with the world like any other, for example with a
+/// [`ParticleGround`](super::collide::ParticleGround) targeting
+/// [`SoftBody::particles`].
+#[derive(Debug, Clone)]
+pub struct SoftBody {
+    particles: Vec<ParticleId>,
+    triangles: Vec<[usize; 3]>,
+    springs: Vec<SoftBodySpring>,
+    particle_mass: Real,
+    rest_volume: Real,
+
+    /// Holds the spring constant of the edges, relative to the mass
+    /// of the particles.
+    pub stiffness: Real,
+    /// Holds the damping of the edges, relative to the mass of the
+    /// particles.
+    pub damping: Real,
+    /// Holds how strongly the body resists changes in its volume.
+    pub pressure: Real,
+}
+
+impl SoftBody {
+    /// Creates the particles of a soft body with the given vertices,
+    /// sharing `mass` between them. The triangles index into the
+    /// vertices, and have to close the mesh and wind
+    /// counter-clockwise when seen from outside.
+    pub fn new(
+        particles: &mut ParticleSet,
+        vertices: &[Vec3],
+        triangles: &[[usize; 3]],
+        mass: Real,
+    ) -> Self {
+        assert!(
+            triangles.iter().flatten().all(|&i| i < vertices.len()),
+            "The triangles of a soft body must index into its vertices"
+        );
+
+        let particle_mass = mass / vertices.len() as Real;
+        let ids = vertices
+            .iter()
+            .map(|&position| particles.insert(Particle::new(particle_mass).with_position(position)))
+            .collect();
+
+        // Edges shared by two triangles get a single spring. The edges
+        // are kept in order, so the springs are applied in the same
+        // order every run.
+        let mut edges = BTreeSet::new();
+        for &[a, b, c] in triangles {
+            for (i, j) in [(a, b), (b, c), (c, a)] {
+                edges.insert((i.min(j), i.max(j)));
+            }
+        }
+        let springs = edges
+            .into_iter()
+            .map(|(particle_a, particle_b)| SoftBodySpring {
+                particle_a,
+                particle_b,
+                rest_length: vertices[particle_a].distance_to(vertices[particle_b]),
+            })
+            .collect();
+
+        Self {
+            particles: ids,
+            triangles: triangles.to_vec(),
+            springs,
+            particle_mass,
+            rest_volume: mesh_volume(vertices, triangles),
+            stiffness: 100.0,
+            damping: 2.0,
+            pressure: 50.0,
+        }
+    }
+
+    /// Creates a soft box, centred on `centre`.
+    pub fn cuboid(particles: &mut ParticleSet, centre: Vec3, half_size: Vec3, mass: Real) -> Self {
+        let vertices: Vec<Vec3> = (0..8)
+            .map(|i| {
+                let corner = Vec3::new(
+                    if i & 1 == 0 { -1.0 } else { 1.0 },
+                    if i & 2 == 0 { -1.0 } else { 1.0 },
+                    if i & 4 == 0 { -1.0 } else { 1.0 },
+                );
+                centre + half_size.component_product(corner)
+            })
+            .collect();
+
+        const TRIANGLES: [[usize; 3]; 12] = [
+            [0, 2, 3],
+            [0, 3, 1],
+            [4, 5, 7],
+            [4, 7, 6],
+            [0, 1, 5],
+            [0, 5, 4],
+            [2, 6, 7],
+            [2, 7, 3],
+            [0, 4, 6],
+            [0, 6, 2],
+            [1, 3, 7],
+            [1, 7, 5],
+        ];
+
+        Self::new(particles, &vertices, &TRIANGLES, mass)
+    }
+
+    pub fn with_stiffness(mut self, stiffness: Real) -> Self {
+        self.stiffness = stiffness;
+        self
+    }
+
+    pub fn with_damping(mut self, damping: Real) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    pub fn with_pressure(mut self, pressure: Real) -> Self {
+        self.pressure = pressure;
+        self
+    }
+
+    /// Returns the particles of the soft body, in the order of the
+    /// vertices it was created with.
+    pub fn particles(&self) -> &[ParticleId] {
+        &self.particles
+    }
+
+    /// Returns the triangles of the soft body, as indices into its
+    /// particles.
+    pub fn triangles(&self) -> &[[usize; 3]] {
+        &self.triangles
+    }
+
+    pub fn rest_volume(&self) -> Real {
+        self.rest_volume
+    }
+
+    /// Calculates the current volume of the soft body.
+    pub fn volume(&self, particles: &ParticleSet) -> Real {
+        mesh_volume(&self.positions(particles), &self.triangles)
+    }
+
+    /// Returns the current positions of the particles, in the order
+    /// of the vertices it was created with.
+    pub fn positions(&self, particles: &ParticleSet) -> Vec<Vec3> {
+        self.particles
+            .iter()
+            .map(|&id| particles[id].position)
+            .collect()
+    }
+}
+
+impl ParticleForceGenerator for SoftBody {
+    fn update_forces(&mut self, particles: &mut ParticleSet, _duration: Real) {
+        for spring in &self.springs {
+            let [particle_a, particle_b] = particles
+                .get_disjoint_mut([
+                    self.particles[spring.particle_a],
+                    self.particles[spring.particle_b],
+                ])
+                .unwrap();
+
+            let delta = particle_a.position - particle_b.position;
+            let direction = delta.normalized();
+            let speed = (particle_a.velocity - particle_b.velocity).dot(direction);
+
+            let force = (-self.stiffness * (delta.magnitude() - spring.rest_length)
+                - self.damping * speed)
+                * self.particle_mass
+                * direction;
+
+            particle_a.add_force(force);
+            particle_b.add_force(-force);
+        }
+
+        // The pressure is the difference from the rest volume, as a
+        // fraction of it, and it acts on every face in proportion to
+        // its area.
+        let positions = self.positions(particles);
+        let volume = mesh_volume(&positions, &self.triangles);
+        if volume <= 0.0 || self.rest_volume <= 0.0 {
+            return;
+        }
+        let pressure = self.pressure * (self.rest_volume / volume - 1.0);
+
+        for &[a, b, c] in &self.triangles {
+            // Half the cross product is the area-weighted normal.
+            let area_normal =
+                (positions[b] - positions[a]).cross(positions[c] - positions[a]) * 0.5;
+            let force = area_normal * (pressure * self.particle_mass / 3.0);
+            for i in [a, b, c] {
+                particles[self.particles[i]].add_force(force);
+            }
+        }
+    }
+}
+
+/// Calculates the volume enclosed by a closed triangle mesh, as the
+/// sum of the signed volumes of the tetrahedra between each face and
+/// the origin.
+fn mesh_volume(positions: &[Vec3], triangles: &[[usize; 3]]) -> Real {
+    triangles
+        .iter()
+        .map(|&[a, b, c]| positions[a].dot(positions[b].cross(positions[c])))
+        .sum::<Real>()
+        / 6.0
+}
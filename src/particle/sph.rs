@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+
+use crate::{consts::PI, precision::Real, rigid_body::collide_narrow::Plane, Vec3};
+
+use super::{
+    collide::grid_cell, fgen::ParticleForceGenerator, ParticleId, ParticleIntegrator, ParticleSet,
+    ParticleTargets,
+};
+
+/// A fluid simulated with smoothed-particle hydrodynamics.
+///
+/// Every particle samples the fluid around it: its density is worked
+/// out from the particles within the smoothing radius, and the
+/// difference in pressure and velocity with them pushes and drags it
+/// along. The fluid is a force generator, and its container is kept
+/// by calling [`SphFluid::enforce_boundaries`] after each step.
+///
+/// The fluid needs short steps to stay stable, usually several per
+/// frame. It samples the particles where
+/// [`ParticleIntegrator::ExplicitEuler`] moves them, so it only works
+/// with that integrator.
+#[derive(Debug, Clone)]
+pub struct SphFluid {
+    pub targets: ParticleTargets,
+    /// Holds the distance over which the particles affect each other.
+    pub smoothing_radius: Real,
+    /// Holds the density the fluid settles at.
+    pub rest_density: Real,
+    /// Holds how strongly the fluid pushes back when it is denser
+    /// than its rest density.
+    pub stiffness: Real,
+    /// Holds how strongly the particles drag each other along.
+    pub viscosity: Real,
+    /// Holds the half-spaces the fluid is kept in.
+    pub boundaries: Vec<Plane>,
+    /// Holds the fraction of their speed into a boundary the
+    /// particles keep, bouncing off it.
+    pub restitution: Real,
+    densities: HashMap<ParticleId, Real>,
+}
+
+impl SphFluid {
+    pub fn new(
+        targets: impl Into<ParticleTargets>,
+        smoothing_radius: Real,
+        rest_density: Real,
+    ) -> Self {
+        Self {
+            targets: targets.into(),
+            smoothing_radius,
+            rest_density,
+            stiffness: 50.0,
+            viscosity: 1.0,
+            boundaries: Vec::new(),
+            restitution: 0.0,
+            densities: HashMap::new(),
+        }
+    }
+
+    pub fn with_stiffness(mut self, stiffness: Real) -> Self {
+        self.stiffness = stiffness;
+        self
+    }
+
+    pub fn with_viscosity(mut self, viscosity: Real) -> Self {
+        self.viscosity = viscosity;
+        self
+    }
+
+    pub fn with_boundary(mut self, plane: Plane) -> Self {
+        self.boundaries.push(plane);
+        self
+    }
+
+    pub fn with_restitution(mut self, restitution: Real) -> Self {
+        self.restitution = restitution;
+        self
+    }
+
+    /// Returns the density of the fluid at the given particle, as of
+    /// the last update.
+    pub fn density(&self, particle: ParticleId) -> Option<Real> {
+        self.densities.get(&particle).copied()
+    }
+
+    /// Moves the particles that left the container back onto its
+    /// walls, and removes their speed into the walls.
+    pub fn enforce_boundaries(&self, particles: &mut ParticleSet) {
+        self.targets.for_each_mut(particles, |_, particle| {
+            for plane in &self.boundaries {
+                let distance = particle.position.dot(plane.normal) - plane.offset - particle.radius;
+                if distance >= 0.0 {
+                    continue;
+                }
+
+                particle.position -= plane.normal * distance;
+
+                let normal_speed = particle.velocity.dot(plane.normal);
+                if normal_speed < 0.0 {
+                    particle.velocity -= plane.normal * ((1.0 + self.restitution) * normal_speed);
+                }
+            }
+        });
+    }
+}
+
+impl ParticleForceGenerator for SphFluid {
+    fn update_forces(&mut self, particles: &mut ParticleSet, duration: Real) {
+        // Particles move with their current velocity before the forces
+        // act on them, so the fluid is sampled where they'll be after
+        // the step. Sampling it where they are makes it gain energy
+        // and clump together.
+        let samples: Vec<(ParticleId, Vec3, Vec3, Real)> = self
+            .targets
+            .iter(particles)
+            .filter(|(_, particle)| particle.inverse_mass > 0.0)
+            .map(|(id, particle)| {
+                let position = particle.position + particle.velocity * duration;
+                (id, position, particle.velocity, particle.mass())
+            })
+            .collect();
+
+        let h = self.smoothing_radius;
+        let h_squared = h * h;
+
+        // The kernels of Müller et al., for the density, the pressure
+        // gradient and the viscosity Laplacian.
+        let poly6 = 315.0 / (64.0 * PI * h.powi(9));
+        let spiky_gradient = -45.0 / (PI * h.powi(6));
+        let viscosity_laplacian = 45.0 / (PI * h.powi(6));
+
+        // Sort the particles into cells as wide as the smoothing
+        // radius, so only the neighbouring cells need checking.
+        let mut grid: HashMap<[i32; 3], Vec<usize>> = HashMap::new();
+        for (index, &(_, position, _, _)) in samples.iter().enumerate() {
+            grid.entry(grid_cell(position, h)).or_default().push(index);
+        }
+
+        let neighbours: Vec<Vec<(usize, Vec3, Real)>> = samples
+            .iter()
+            .map(|&(_, position, _, _)| {
+                let [x, y, z] = grid_cell(position, h);
+                let mut found = Vec::new();
+                for dx in -1..=1 {
+                    for dy in -1..=1 {
+                        for dz in -1..=1 {
+                            let Some(cell) = grid.get(&[x + dx, y + dy, z + dz]) else {
+                                continue;
+                            };
+
+                            for &other in cell {
+                                let offset = position - samples[other].1;
+                                let distance_squared = offset.squared_magnitude();
+                                if distance_squared < h_squared {
+                                    found.push((other, offset, distance_squared));
+                                }
+                            }
+                        }
+                    }
+                }
+                found
+            })
+            .collect();
+
+        // Work out the density and pressure at every particle. Each
+        // particle counts towards its own density.
+        let densities: Vec<Real> = neighbours
+            .iter()
+            .map(|found| {
+                found
+                    .iter()
+                    .map(|&(other, _, distance_squared)| {
+                        samples[other].3 * poly6 * (h_squared - distance_squared).powi(3)
+                    })
+                    .sum()
+            })
+            .collect();
+        let pressures: Vec<Real> = densities
+            .iter()
+            .map(|&density| self.stiffness * (density - self.rest_density).max(0.0))
+            .collect();
+
+        for (i, found) in neighbours.iter().enumerate() {
+            let (id, _, velocity, mass) = samples[i];
+            let mut pressure_force = Vec3::ZERO;
+            let mut viscosity_force = Vec3::ZERO;
+
+            for &(j, offset, distance_squared) in found {
+                if j == i || distance_squared <= 0.0 {
+                    continue;
+                }
+
+                let distance = distance_squared.sqrt();
+                let (_, _, other_velocity, other_mass) = samples[j];
+                let falloff = h - distance;
+
+                pressure_force -= offset
+                    * (other_mass * (pressures[i] + pressures[j]) / (2.0 * densities[j])
+                        * spiky_gradient
+                        * falloff
+                        * falloff
+                        / distance);
+                viscosity_force += (other_velocity - velocity)
+                    * (other_mass / densities[j] * viscosity_laplacian * falloff);
+            }
+
+            // The forces are per unit of volume, so they are scaled by
+            // the volume the particle takes up.
+            let force = (pressure_force + viscosity_force * self.viscosity) * (mass / densities[i]);
+            particles[id].add_force(force);
+        }
+
+        self.densities = samples
+            .iter()
+            .zip(densities)
+            .map(|(&(id, _, _, _), density)| (id, density))
+            .collect();
+    }
+
+    fn supports_integrator(&self, integrator: ParticleIntegrator) -> bool {
+        integrator == ParticleIntegrator::ExplicitEuler
+    }
+}
@@ -0,0 +1,69 @@
+use slotmap::SecondaryMap;
+
+use crate::{precision::Real, timestep::FixedTimestep, Vec3};
+
+use super::{ParticleId, ParticleSet};
+
+/// Steps particles at a fixed rate from a render loop that runs at
+/// any rate, and interpolates their positions for rendering.
+///
+/// This works like the rigid body
+/// [`FixedStepper`](crate::rigid_body::FixedStepper), for particles.
+#[derive(Debug, Clone)]
+pub struct ParticleFixedStepper {
+    timestep: FixedTimestep,
+    previous: SecondaryMap<ParticleId, Vec3>,
+}
+
+impl ParticleFixedStepper {
+    pub fn new(step: Real) -> Self {
+        Self {
+            timestep: FixedTimestep::new(step),
+            previous: SecondaryMap::new(),
+        }
+    }
+
+    pub fn with_max_steps(mut self, max_steps: u32) -> Self {
+        self.timestep = self.timestep.with_max_steps(max_steps);
+        self
+    }
+
+    pub fn timestep(&self) -> &FixedTimestep {
+        &self.timestep
+    }
+
+    /// Adds the time of a frame, and calls `step` with the fixed
+    /// duration for every step it allows. The closure should run a
+    /// whole step of the simulation: starting the frame, generating
+    /// contacts and stepping the system. Returns the number of steps
+    /// taken.
+    pub fn update(
+        &mut self,
+        particles: &mut ParticleSet,
+        frame_time: Real,
+        mut step: impl FnMut(&mut ParticleSet, Real),
+    ) -> u32 {
+        let steps = self.timestep.advance(frame_time);
+
+        for _ in 0..steps {
+            self.previous.clear();
+            for (id, particle) in particles.iter() {
+                self.previous.insert(id, particle.position);
+            }
+
+            step(particles, self.timestep.step());
+        }
+
+        steps
+    }
+
+    /// Returns the position of the particle to render, between its
+    /// last two steps.
+    pub fn interpolated_position(&self, particles: &ParticleSet, particle: ParticleId) -> Vec3 {
+        let current = particles[particle].position;
+        match self.previous.get(particle) {
+            Some(&previous) => previous.lerp(current, self.timestep.alpha()),
+            None => current,
+        }
+    }
+}
@@ -0,0 +1,332 @@
+use crate::{precision::Real, Vec3};
+
+use super::{
+    contacts::{ParticleContact, ParticleContactGenerator},
+    fgen::{ForceGeneratorId, ParticleForceGeneratorSet, ParticleSpring},
+    links::{ParticleCable, ParticleCableConstraint, ParticleRod},
+    Particle, ParticleGroup, ParticleId, ParticleSet,
+};
+
+/// The kind of link a builder joins neighbouring particles with. The
+/// length of every link is the distance between its particles when
+/// they are created.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StructureLink {
+    Rod,
+    Cable {
+        restitution: Real,
+    },
+    Spring {
+        spring_constant: Real,
+        damping: Real,
+    },
+}
+
+/// The particles and links created by a builder.
+///
+/// Rods, cables and anchors are contact generators, so the structure
+/// has to be passed to
+/// [`ParticlePhysicsSystem::generate_contacts`](super::ParticlePhysicsSystem::generate_contacts)
+/// every frame. Springs are inserted into the force generator set
+/// given to the builder.
+#[derive(Debug, Clone, Default)]
+pub struct ParticleStructure {
+    pub particles: Vec<ParticleId>,
+    pub rods: Vec<ParticleRod>,
+    pub cables: Vec<ParticleCable>,
+    pub springs: Vec<ForceGeneratorId>,
+    pub anchors: Vec<ParticleCableConstraint>,
+}
+
+impl ParticleStructure {
+    fn link(
+        &mut self,
+        link: StructureLink,
+        particle_a: ParticleId,
+        particle_b: ParticleId,
+        particles: &ParticleSet,
+        generators: &mut ParticleForceGeneratorSet,
+    ) {
+        let length = particles[particle_a]
+            .position
+            .distance_to(particles[particle_b].position);
+
+        match link {
+            StructureLink::Rod => self.rods.push(ParticleRod {
+                particle_a,
+                particle_b,
+                length,
+            }),
+            StructureLink::Cable { restitution } => self.cables.push(ParticleCable {
+                particle_a,
+                particle_b,
+                max_length: length,
+                restitution,
+            }),
+            StructureLink::Spring {
+                spring_constant,
+                damping,
+            } => self.springs.push(
+                generators.insert(
+                    ParticleSpring::new(particle_a, particle_b, spring_constant, length)
+                        .with_damping(damping),
+                ),
+            ),
+        }
+    }
+
+    fn anchor(&mut self, particle: ParticleId, particles: &ParticleSet) {
+        self.anchors.push(ParticleCableConstraint {
+            particle_a: particle,
+            anchor: particles[particle].position,
+            max_length: 0.0,
+            restitution: 0.0,
+        });
+    }
+}
+
+impl ParticleContactGenerator for ParticleStructure {
+    fn add_contacts(&self, contacts: &mut [ParticleContact], particles: &ParticleSet) -> usize {
+        let mut used = self.rods.add_contacts(contacts, particles);
+        used += self.cables.add_contacts(&mut contacts[used..], particles);
+        used += self.anchors.add_contacts(&mut contacts[used..], particles);
+        used
+    }
+}
+
+/// Builds a rope or chain: a line of particles with a link between
+/// every consecutive pair.
+#[derive(Debug, Clone)]
+pub struct ParticleRope {
+    pub start: Vec3,
+    pub end: Vec3,
+    /// Holds the number of links. The rope has one more particle.
+    pub segments: usize,
+    pub particle_mass: Real,
+    pub link: StructureLink,
+    pub groups: ParticleGroup,
+    pub anchor_start: bool,
+    pub anchor_end: bool,
+}
+
+impl ParticleRope {
+    pub fn new(start: Vec3, end: Vec3, segments: usize) -> Self {
+        assert!(segments > 0, "A rope needs at least one segment");
+        Self {
+            start,
+            end,
+            segments,
+            particle_mass: 1.0,
+            link: StructureLink::Rod,
+            groups: ParticleGroup::NONE,
+            anchor_start: false,
+            anchor_end: false,
+        }
+    }
+
+    pub fn with_particle_mass(mut self, particle_mass: Real) -> Self {
+        self.particle_mass = particle_mass;
+        self
+    }
+
+    pub fn with_link(mut self, link: StructureLink) -> Self {
+        self.link = link;
+        self
+    }
+
+    pub fn with_groups(mut self, groups: ParticleGroup) -> Self {
+        self.groups = groups;
+        self
+    }
+
+    /// Anchors the first particle where it starts.
+    pub fn with_anchored_start(mut self) -> Self {
+        self.anchor_start = true;
+        self
+    }
+
+    /// Anchors the last particle where it starts.
+    pub fn with_anchored_end(mut self) -> Self {
+        self.anchor_end = true;
+        self
+    }
+
+    /// Creates the particles, from the start to the end of the rope,
+    /// and their links.
+    pub fn build(
+        &self,
+        particles: &mut ParticleSet,
+        generators: &mut ParticleForceGeneratorSet,
+    ) -> ParticleStructure {
+        let mut structure = ParticleStructure::default();
+        let step = (self.end - self.start) / self.segments as Real;
+
+        for i in 0..=self.segments {
+            let particle = Particle::new(self.particle_mass)
+                .with_position(self.start + step * i as Real)
+                .with_groups(self.groups);
+            structure.particles.push(particles.insert(particle));
+        }
+
+        for i in 0..self.segments {
+            let (a, b) = (structure.particles[i], structure.particles[i + 1]);
+            structure.link(self.link, a, b, particles, generators);
+        }
+
+        if self.anchor_start {
+            structure.anchor(structure.particles[0], particles);
+        }
+        if self.anchor_end {
+            structure.anchor(structure.particles[self.segments], particles);
+        }
+
+        structure
+    }
+}
+
+/// Builds a lattice of particles: a grid of particles along three
+/// axes, linked to their neighbours along the axes and, optionally,
+/// across the diagonals of every cell to brace it.
+///
+/// The particles of the structure are ordered along the first axis,
+/// then the second, then the third, so the particle at `(x, y, z)`
+/// is at index `x + y * counts[0] + z * counts[0] * counts[1]`.
+#[derive(Debug, Clone)]
+pub struct ParticleLattice {
+    pub origin: Vec3,
+    /// Holds the offset between neighbouring particles along each
+    /// axis.
+    pub axes: [Vec3; 3],
+    /// Holds the number of particles along each axis.
+    pub counts: [usize; 3],
+    pub particle_mass: Real,
+    pub link: StructureLink,
+    pub groups: ParticleGroup,
+    pub braced: bool,
+}
+
+impl ParticleLattice {
+    pub fn new(origin: Vec3, axes: [Vec3; 3], counts: [usize; 3]) -> Self {
+        assert!(
+            counts.iter().all(|&count| count > 0),
+            "A lattice needs at least one particle along each axis"
+        );
+        Self {
+            origin,
+            axes,
+            counts,
+            particle_mass: 1.0,
+            link: StructureLink::Rod,
+            groups: ParticleGroup::NONE,
+            braced: true,
+        }
+    }
+
+    /// Creates a flat lattice, such as a piece of cloth, with
+    /// `columns` particles along `u` and `rows` along `v`.
+    pub fn grid(origin: Vec3, u: Vec3, v: Vec3, columns: usize, rows: usize) -> Self {
+        Self::new(origin, [u, v, u.cross(v).normalized()], [columns, rows, 1])
+    }
+
+    pub fn with_particle_mass(mut self, particle_mass: Real) -> Self {
+        self.particle_mass = particle_mass;
+        self
+    }
+
+    pub fn with_link(mut self, link: StructureLink) -> Self {
+        self.link = link;
+        self
+    }
+
+    pub fn with_groups(mut self, groups: ParticleGroup) -> Self {
+        self.groups = groups;
+        self
+    }
+
+    /// Sets whether the cells are braced with diagonal links. Without
+    /// them the lattice shears freely.
+    pub fn with_bracing(mut self, braced: bool) -> Self {
+        self.braced = braced;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.counts.iter().product()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the index of the particle at the given lattice
+    /// coordinates.
+    pub fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        x + y * self.counts[0] + z * self.counts[0] * self.counts[1]
+    }
+
+    pub fn build(
+        &self,
+        particles: &mut ParticleSet,
+        generators: &mut ParticleForceGeneratorSet,
+    ) -> ParticleStructure {
+        // Every pair of neighbours is linked once, from the particle
+        // with the lower coordinates along the first axis that
+        // differs.
+        const AXIS_OFFSETS: [[isize; 3]; 3] = [[1, 0, 0], [0, 1, 0], [0, 0, 1]];
+        const DIAGONAL_OFFSETS: [[isize; 3]; 10] = [
+            [1, 1, 0],
+            [1, -1, 0],
+            [1, 0, 1],
+            [1, 0, -1],
+            [0, 1, 1],
+            [0, 1, -1],
+            [1, 1, 1],
+            [1, 1, -1],
+            [1, -1, 1],
+            [1, -1, -1],
+        ];
+
+        let mut structure = ParticleStructure::default();
+        let [cx, cy, cz] = self.counts;
+
+        for z in 0..cz {
+            for y in 0..cy {
+                for x in 0..cx {
+                    let position = self.origin
+                        + self.axes[0] * x as Real
+                        + self.axes[1] * y as Real
+                        + self.axes[2] * z as Real;
+                    let particle = Particle::new(self.particle_mass)
+                        .with_position(position)
+                        .with_groups(self.groups);
+                    structure.particles.push(particles.insert(particle));
+                }
+            }
+        }
+
+        let diagonals: &[[isize; 3]] = if self.braced { &DIAGONAL_OFFSETS } else { &[] };
+        for z in 0..cz {
+            for y in 0..cy {
+                for x in 0..cx {
+                    let a = structure.particles[self.index(x, y, z)];
+
+                    for offset in AXIS_OFFSETS.iter().chain(diagonals) {
+                        let neighbour = [
+                            x.checked_add_signed(offset[0]).filter(|&x| x < cx),
+                            y.checked_add_signed(offset[1]).filter(|&y| y < cy),
+                            z.checked_add_signed(offset[2]).filter(|&z| z < cz),
+                        ];
+                        let [Some(nx), Some(ny), Some(nz)] = neighbour else {
+                            continue;
+                        };
+
+                        let b = structure.particles[self.index(nx, ny, nz)];
+                        structure.link(self.link, a, b, particles, generators);
+                    }
+                }
+            }
+        }
+
+        structure
+    }
+}
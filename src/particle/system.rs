@@ -1,9 +1,9 @@
-use crate::precision::Real;
+use crate::{precision::Real, Vec3};
 
 use super::{
     contacts::{ParticleContact, ParticleContactGenerator, ParticleContactResolver},
-    fgen::ParticleForceGeneratorSet,
-    ParticleSet,
+    fgen::{ForceGeneratorId, ParticleForceGeneratorSet},
+    ParticleId, ParticleIntegrator, ParticleSet,
 };
 
 #[derive(Debug, Clone)]
@@ -12,6 +12,9 @@ pub struct ParticlePhysicsSystem {
     contacts: Vec<ParticleContact>,
     contacts_used: usize,
     calculate_iterations: bool,
+    integrator: ParticleIntegrator,
+    substeps: u32,
+    broken_generators: Vec<ForceGeneratorId>,
 }
 
 impl ParticlePhysicsSystem {
@@ -21,21 +24,93 @@ impl ParticlePhysicsSystem {
             contacts: vec![ParticleContact::default(); max_contacts],
             contacts_used: 0,
             calculate_iterations: iterations == 0,
+            integrator: ParticleIntegrator::default(),
+            substeps: 1,
+            broken_generators: Vec::new(),
         }
     }
 
+    /// Sets the integrator the particles are moved with. Stepping
+    /// with force generators that don't work with it panics before
+    /// any forces are applied.
+    pub fn with_integrator(mut self, integrator: ParticleIntegrator) -> Self {
+        self.integrator = integrator;
+        self
+    }
+
+    /// Sets the number of passes each step is split into. The force
+    /// generators are run again for every pass, which keeps stiff
+    /// springs stable without shortening the steps themselves.
+    pub fn with_substeps(mut self, substeps: u32) -> Self {
+        assert!(substeps > 0, "A step needs at least one substep");
+        self.substeps = substeps;
+        self
+    }
+
+    /// Applies the force generators and integrates the particles, in
+    /// as many substeps as the system has, and then resolves the
+    /// contacts generated for this frame.
+    ///
+    /// The contacts are only generated once, before the step, so
+    /// they're resolved once at the end of it. To collide the
+    /// particles again in every substep, use
+    /// [`ParticlePhysicsSystem::step_with_contacts`] instead.
     pub fn step(
         &mut self,
         particles: &mut ParticleSet,
         generators: &mut ParticleForceGeneratorSet,
         duration: Real,
     ) {
-        generators.update_forces(particles, duration);
+        generators.assert_supports_integrator(self.integrator);
 
-        for particle in particles.particles_mut() {
-            particle.integrate(duration);
+        let substep = duration / self.substeps as Real;
+        let forces = accumulated_forces(particles);
+
+        self.broken_generators.clear();
+        for index in 0..self.substeps {
+            if index > 0 {
+                restore_forces(particles, &forces);
+            }
+
+            let broken = self.integrator.integrate(particles, generators, substep);
+            self.broken_generators.extend(broken);
+        }
+
+        self.resolve_contacts(particles, duration);
+    }
+
+    /// Steps the particles like [`ParticlePhysicsSystem::step`], but
+    /// generates and resolves the contacts again in every substep.
+    /// Any contacts generated before the step are discarded.
+    pub fn step_with_contacts(
+        &mut self,
+        particles: &mut ParticleSet,
+        generators: &mut ParticleForceGeneratorSet,
+        cg: &impl ParticleContactGenerator,
+        duration: Real,
+    ) {
+        generators.assert_supports_integrator(self.integrator);
+
+        let substep = duration / self.substeps as Real;
+        let forces = accumulated_forces(particles);
+
+        self.broken_generators.clear();
+        for index in 0..self.substeps {
+            if index > 0 {
+                restore_forces(particles, &forces);
+            }
+
+            self.contacts_used = 0;
+            self.generate_contacts(cg, particles);
+
+            let broken = self.integrator.integrate(particles, generators, substep);
+            self.broken_generators.extend(broken);
+
+            self.resolve_contacts(particles, substep);
         }
+    }
 
+    fn resolve_contacts(&mut self, particles: &mut ParticleSet, duration: Real) {
         if self.contacts_used > 0 {
             if self.calculate_iterations {
                 self.resolver.iterations = (self.contacts_used * 2) as u32;
@@ -48,10 +123,18 @@ impl ParticlePhysicsSystem {
         }
     }
 
+    /// Returns the force generators that broke during the last step.
+    pub fn broken_generators(&self) -> &[ForceGeneratorId] {
+        &self.broken_generators
+    }
+
     pub fn start_frame(&mut self, particles: &mut ParticleSet) {
         for particle in particles.particles_mut() {
             particle.clear_accumulator();
         }
+
+        // Contacts are generated afresh every frame.
+        self.contacts_used = 0;
     }
 
     pub fn generate_contacts(
@@ -68,3 +151,21 @@ impl ParticlePhysicsSystem {
         self.contacts_used += used;
     }
 }
+
+/// Returns the forces added to the particles before a step, so they
+/// can be applied again in every substep.
+fn accumulated_forces(particles: &ParticleSet) -> Vec<(ParticleId, Vec3)> {
+    particles
+        .iter()
+        .filter(|(_, particle)| particle.force_accum != Vec3::ZERO)
+        .map(|(id, particle)| (id, particle.force_accum))
+        .collect()
+}
+
+fn restore_forces(particles: &mut ParticleSet, forces: &[(ParticleId, Vec3)]) {
+    for &(id, force) in forces {
+        if let Some(particle) = particles.get_mut(id) {
+            particle.force_accum = force;
+        }
+    }
+}
@@ -38,6 +38,8 @@ pub enum PrimitiveShape {
     Sphere(Sphere),
     Plane(Plane),
     Cuboid(Cuboid),
+    Capsule(Capsule),
+    Cylinder(Cylinder),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -56,6 +58,43 @@ pub struct Cuboid {
     pub half_size: Vec3,
 }
 
+/// A cylinder with hemispherical caps, standing along its local Y
+/// axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Capsule {
+    pub radius: Real,
+    /// Holds half the height of the cylinder between the caps.
+    pub half_height: Real,
+}
+
+/// A cylinder standing along its local Y axis.
+///
+/// Cylinders give bodies their mass properties, and collide with
+/// particles, but there are no routines below to collide them with
+/// other rigid bodies.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cylinder {
+    pub radius: Real,
+    pub half_height: Real,
+}
+
+impl Collider {
+    /// Moves every primitive of the collider by the given offset, in
+    /// body space.
+    pub fn translate(&mut self, offset: Vec3) {
+        let primitives = match self {
+            Collider::Single(primitive) => std::slice::from_mut(primitive),
+            Collider::Set(primitives) => primitives.as_mut_slice(),
+        };
+
+        for primitive in primitives {
+            primitive.offset.data[3] += offset.x;
+            primitive.offset.data[7] += offset.y;
+            primitive.offset.data[11] += offset.z;
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct CollisionData<'contacts> {
     pub body_a: RigidBodyId,
@@ -436,4 +475,249 @@ pub mod algo {
             penetration: overlap,
         });
     }
+
+    pub fn capsule_and_sphere(
+        capsule: Capsule,
+        capsule_transform: &Mat4,
+        sphere: Sphere,
+        sphere_transform: &Mat4,
+        data: CollisionData,
+    ) {
+        let centre = sphere_transform.get_position();
+        let (start, end) = capsule_segment(capsule, capsule_transform);
+        let closest = closest_point_on_segment(start, end, centre);
+
+        add_rounded_contact(closest, capsule.radius, centre, sphere.radius, data);
+    }
+
+    /// Collides each end of the capsule with the plane, so a capsule
+    /// lying on it gets a contact at both ends.
+    pub fn capsule_and_half_space(
+        capsule: Capsule,
+        capsule_transform: &Mat4,
+        plane: Plane,
+        data: CollisionData,
+    ) {
+        let (start, end) = capsule_segment(capsule, capsule_transform);
+        for centre in [start, end] {
+            let distance = centre.dot(plane.normal) - capsule.radius - plane.offset;
+            if distance >= 0.0 {
+                continue;
+            }
+
+            data.contacts.push(Contact {
+                body_a: data.body_a,
+                body_b: data.body_b,
+                point: centre - plane.normal * (distance + capsule.radius),
+                normal: plane.normal,
+                penetration: -distance,
+            });
+        }
+    }
+
+    pub fn capsule_and_capsule(
+        capsule_a: Capsule,
+        transform_a: &Mat4,
+        capsule_b: Capsule,
+        transform_b: &Mat4,
+        data: CollisionData,
+    ) {
+        let (start_a, end_a) = capsule_segment(capsule_a, transform_a);
+        let (start_b, end_b) = capsule_segment(capsule_b, transform_b);
+        let (closest_a, closest_b) = closest_points_on_segments(start_a, end_a, start_b, end_b);
+
+        add_rounded_contact(
+            closest_a,
+            capsule_a.radius,
+            closest_b,
+            capsule_b.radius,
+            data,
+        );
+    }
+
+    /// Returns the ends of the segment running through the middle of
+    /// the capsule, in world space.
+    fn capsule_segment(capsule: Capsule, transform: &Mat4) -> (Vec3, Vec3) {
+        let half = Vec3::new(0.0, capsule.half_height, 0.0);
+        (transform.transform(-half), transform.transform(half))
+    }
+
+    fn closest_point_on_segment(start: Vec3, end: Vec3, point: Vec3) -> Vec3 {
+        let direction = end - start;
+        let length_squared = direction.squared_magnitude();
+        if length_squared <= 0.0 {
+            return start;
+        }
+
+        let t = ((point - start).dot(direction) / length_squared).clamp(0.0, 1.0);
+        start + direction * t
+    }
+
+    /// Returns the closest points between two segments, one on each.
+    fn closest_points_on_segments(
+        start_a: Vec3,
+        end_a: Vec3,
+        start_b: Vec3,
+        end_b: Vec3,
+    ) -> (Vec3, Vec3) {
+        let direction_a = end_a - start_a;
+        let direction_b = end_b - start_b;
+        let length_squared_a = direction_a.squared_magnitude();
+        let length_squared_b = direction_b.squared_magnitude();
+
+        if length_squared_a <= 0.0 {
+            return (start_a, closest_point_on_segment(start_b, end_b, start_a));
+        }
+        if length_squared_b <= 0.0 {
+            return (closest_point_on_segment(start_a, end_a, start_b), start_b);
+        }
+
+        // Find where the lines through the segments come closest, and
+        // clamp that to the segments. Parallel segments can take any
+        // point on A, so they start from its first end.
+        let offset = start_a - start_b;
+        let dot_product = direction_a.dot(direction_b);
+        let denom = length_squared_a * length_squared_b - dot_product * dot_product;
+        let mut t_a = if denom > 0.0 {
+            ((dot_product * direction_b.dot(offset) - length_squared_b * direction_a.dot(offset))
+                / denom)
+                .clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        // Find the closest point on B to that, and then the closest
+        // point on A to that in turn, if B had to be clamped.
+        let mut t_b = (dot_product * t_a + direction_b.dot(offset)) / length_squared_b;
+        if !(0.0..=1.0).contains(&t_b) {
+            t_b = t_b.clamp(0.0, 1.0);
+            t_a =
+                ((dot_product * t_b - direction_a.dot(offset)) / length_squared_a).clamp(0.0, 1.0);
+        }
+
+        (start_a + direction_a * t_a, start_b + direction_b * t_b)
+    }
+
+    /// Adds the contact between two spheres, for the rounded parts of
+    /// the shapes closest to each other.
+    fn add_rounded_contact(
+        centre_a: Vec3,
+        radius_a: Real,
+        centre_b: Vec3,
+        radius_b: Real,
+        data: CollisionData,
+    ) {
+        let midline = centre_a - centre_b;
+        let distance = midline.magnitude();
+        if distance <= 0.0 || distance > radius_a + radius_b {
+            return;
+        }
+
+        // The contact point is halfway between the two surfaces.
+        let normal = midline / distance;
+        let surface_a = centre_a - normal * radius_a;
+        let surface_b = centre_b + normal * radius_b;
+        data.contacts.push(Contact {
+            body_a: data.body_a,
+            body_b: data.body_b,
+            point: (surface_a + surface_b) * 0.5,
+            normal,
+            penetration: radius_a + radius_b - distance,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{consts::PI, Quat};
+
+    use super::{algo::*, *};
+
+    const CAPSULE: Capsule = Capsule {
+        radius: 0.5,
+        half_height: 1.0,
+    };
+
+    /// Returns the transform of a capsule lying along the X axis.
+    fn lying_at(position: Vec3) -> Mat4 {
+        let half_angle = PI / 4.0;
+        Mat4::from_orientation_and_position(
+            Quat::from_rijk(half_angle.cos(), 0.0, 0.0, half_angle.sin()),
+            position,
+        )
+    }
+
+    fn collide(collide: impl FnOnce(CollisionData)) -> Vec<Contact> {
+        let mut contacts = Vec::new();
+        collide(CollisionData {
+            body_a: RigidBodyId::default(),
+            body_b: None,
+            contacts: &mut contacts,
+        });
+        contacts
+    }
+
+    #[test]
+    fn capsule_touches_sphere_beside_its_side() {
+        let sphere = Sphere { radius: 0.5 };
+        let sphere_transform =
+            Mat4::from_orientation_and_position(Quat::IDENTITY, Vec3::new(0.8, 0.5, 0.0));
+        let contacts = collide(|data| {
+            capsule_and_sphere(CAPSULE, &Mat4::IDENTITY, sphere, &sphere_transform, data)
+        });
+
+        assert_eq!(contacts.len(), 1);
+        assert!((contacts[0].penetration - 0.2).abs() < 1e-5);
+        assert!((contacts[0].normal - Vec3::NEG_X).magnitude() < 1e-5);
+        assert!((contacts[0].point - Vec3::new(0.4, 0.5, 0.0)).magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn capsule_lying_on_plane_touches_at_both_ends() {
+        let plane = Plane {
+            normal: Vec3::Y,
+            offset: 0.0,
+        };
+        let contacts = collide(|data| {
+            capsule_and_half_space(CAPSULE, &lying_at(Vec3::new(0.0, 0.4, 0.0)), plane, data)
+        });
+
+        assert_eq!(contacts.len(), 2);
+        for contact in contacts {
+            assert!((contact.penetration - 0.1).abs() < 1e-5);
+            assert_eq!(contact.normal, Vec3::Y);
+        }
+    }
+
+    #[test]
+    fn crossed_capsules_touch_where_they_cross() {
+        let contacts = collide(|data| {
+            capsule_and_capsule(
+                CAPSULE,
+                &Mat4::IDENTITY,
+                CAPSULE,
+                &lying_at(Vec3::new(0.0, 0.0, 0.8)),
+                data,
+            )
+        });
+
+        assert_eq!(contacts.len(), 1);
+        assert!((contacts[0].penetration - 0.2).abs() < 1e-5);
+        assert!((contacts[0].normal - Vec3::NEG_Z).magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn separate_capsules_do_not_touch() {
+        let contacts = collide(|data| {
+            capsule_and_capsule(
+                CAPSULE,
+                &lying_at(Vec3::ZERO),
+                CAPSULE,
+                &lying_at(Vec3::new(0.0, 1.5, 0.0)),
+                data,
+            )
+        });
+
+        assert!(contacts.is_empty());
+    }
 }
@@ -0,0 +1,248 @@
+use crate::{
+    precision::Real,
+    rigid_body::{RigidBody, RigidBodyId, RigidBodySet},
+    Mat3, Mat4, Quat, Vec3,
+};
+
+use super::ForceGenerator;
+
+/// A force generator that applies an aerodynamic force.
+///
+/// The force depends on the velocity of the air relative to the
+/// body, converted into body space and transformed by the
+/// aerodynamic tensor.
+#[derive(Debug, Clone)]
+pub struct Aero {
+    pub target: RigidBodyId,
+    /// Holds the aerodynamic tensor for the surface in body space.
+    pub tensor: Mat3,
+    /// Holds the relative position of the aerodynamic surface in
+    /// body coordinates.
+    pub position: Vec3,
+    /// Holds the velocity of the air in world space.
+    pub wind_speed: Vec3,
+}
+
+impl Aero {
+    pub fn new(target: RigidBodyId, tensor: Mat3, position: Vec3) -> Self {
+        Self {
+            target,
+            tensor,
+            position,
+            wind_speed: Vec3::ZERO,
+        }
+    }
+
+    pub fn with_wind_speed(mut self, wind_speed: Vec3) -> Self {
+        self.wind_speed = wind_speed;
+        self
+    }
+}
+
+impl ForceGenerator for Aero {
+    fn update_forces(&mut self, bodies: &mut RigidBodySet, _duration: Real) {
+        let body = &mut bodies[self.target];
+        let transform = body.transform_matrix();
+        add_aero_force(body, transform, self.tensor, self.position, self.wind_speed);
+    }
+}
+
+/// A force generator with a control aerodynamic surface. This
+/// requires three inertia tensors, for the two extremes and
+/// 'resting' position of the control surface. The latter tensor is
+/// the one used when the control is centred.
+#[derive(Debug, Clone)]
+pub struct AeroControl {
+    pub target: RigidBodyId,
+    /// Holds the aerodynamic tensor for the surface, when the
+    /// control is at rest.
+    pub tensor: Mat3,
+    /// Holds the aerodynamic tensor for the surface, when the
+    /// control is at its minimum value.
+    pub min_tensor: Mat3,
+    /// Holds the aerodynamic tensor for the surface, when the
+    /// control is at its maximum value.
+    pub max_tensor: Mat3,
+    /// Holds the relative position of the aerodynamic surface in
+    /// body coordinates.
+    pub position: Vec3,
+    /// Holds the velocity of the air in world space.
+    pub wind_speed: Vec3,
+    /// The current position of the control for this surface. This
+    /// should range between -1 (in which case the min_tensor value
+    /// is used), through 0 (where the base-class tensor value is
+    /// used) to +1 (where the max_tensor value is used).
+    control_setting: Real,
+}
+
+impl AeroControl {
+    pub fn new(
+        target: RigidBodyId,
+        tensor: Mat3,
+        min_tensor: Mat3,
+        max_tensor: Mat3,
+        position: Vec3,
+    ) -> Self {
+        Self {
+            target,
+            tensor,
+            min_tensor,
+            max_tensor,
+            position,
+            wind_speed: Vec3::ZERO,
+            control_setting: 0.0,
+        }
+    }
+
+    pub fn with_wind_speed(mut self, wind_speed: Vec3) -> Self {
+        self.wind_speed = wind_speed;
+        self
+    }
+
+    pub fn control_setting(&self) -> Real {
+        self.control_setting
+    }
+
+    /// Sets the control position of this control. This should range
+    /// between -1 (in which case the min_tensor value is used),
+    /// through 0 (where the base-class tensor value is used) to +1
+    /// (where the max_tensor value is used). Values outside that
+    /// range are clamped.
+    pub fn set_control(&mut self, value: Real) {
+        self.control_setting = value.clamp(-1.0, 1.0);
+    }
+
+    /// Calculates the final aerodynamic tensor for the current
+    /// control setting.
+    pub fn current_tensor(&self) -> Mat3 {
+        if self.control_setting < 0.0 {
+            self.min_tensor
+                .lerp(self.tensor, self.control_setting + 1.0)
+        } else if self.control_setting > 0.0 {
+            self.tensor.lerp(self.max_tensor, self.control_setting)
+        } else {
+            self.tensor
+        }
+    }
+}
+
+impl ForceGenerator for AeroControl {
+    fn update_forces(&mut self, bodies: &mut RigidBodySet, _duration: Real) {
+        let body = &mut bodies[self.target];
+        let transform = body.transform_matrix();
+        add_aero_force(
+            body,
+            transform,
+            self.current_tensor(),
+            self.position,
+            self.wind_speed,
+        );
+    }
+}
+
+/// A force generator with an aerodynamic surface that is turned
+/// relative to the body it is attached to, such as a wing mounted
+/// at an angle of incidence. The tensor is given in the space of
+/// the surface, rather than the space of the body.
+#[derive(Debug, Clone)]
+pub struct AngledAero {
+    pub target: RigidBodyId,
+    /// Holds the aerodynamic tensor for the surface in its own space.
+    pub tensor: Mat3,
+    /// Holds the orientation of the surface relative to the body.
+    pub orientation: Quat,
+    /// Holds the relative position of the aerodynamic surface in
+    /// body coordinates.
+    pub position: Vec3,
+    /// Holds the velocity of the air in world space.
+    pub wind_speed: Vec3,
+}
+
+impl AngledAero {
+    pub fn new(target: RigidBodyId, tensor: Mat3, orientation: Quat, position: Vec3) -> Self {
+        Self {
+            target,
+            tensor,
+            orientation,
+            position,
+            wind_speed: Vec3::ZERO,
+        }
+    }
+
+    pub fn with_wind_speed(mut self, wind_speed: Vec3) -> Self {
+        self.wind_speed = wind_speed;
+        self
+    }
+}
+
+impl ForceGenerator for AngledAero {
+    fn update_forces(&mut self, bodies: &mut RigidBodySet, _duration: Real) {
+        let body = &mut bodies[self.target];
+
+        // The surface's frame is its orientation offset, applied on
+        // top of the body's own transform.
+        let offset = Mat4::from_orientation_and_position(self.orientation.normalized(), Vec3::ZERO);
+        let transform = body.transform_matrix().mul_mat4(offset);
+
+        add_aero_force(body, transform, self.tensor, self.position, self.wind_speed);
+    }
+}
+
+/// Uses the given tensor, expressed in the space of the given
+/// transform, to apply the aerodynamic force at the given body-space
+/// point.
+fn add_aero_force(
+    body: &mut RigidBody,
+    transform: Mat4,
+    tensor: Mat3,
+    position: Vec3,
+    wind_speed: Vec3,
+) {
+    // Calculate the velocity of the body through the air.
+    let velocity = body.velocity - wind_speed;
+
+    // Calculate the velocity in surface coordinates.
+    let surface_velocity = transform.transform_inverse_direction(velocity);
+
+    // Calculate the force in surface coordinates, and bring it back
+    // into world space.
+    let surface_force = tensor.transform(surface_velocity);
+    let force = transform.transform_direction(surface_force);
+
+    // Apply the force.
+    body.add_force_at_body_point(force, position);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aero_force(velocity: Vec3, wind_speed: Vec3) -> Vec3 {
+        let mut bodies = RigidBodySet::new();
+        let body = bodies.insert(RigidBody::new(1.0));
+        bodies[body].velocity = velocity;
+        bodies[body].update_derived_data();
+
+        let tensor = Mat3::new([-0.1, 0.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0, -0.1]);
+        Aero::new(body, tensor, Vec3::ZERO)
+            .with_wind_speed(wind_speed)
+            .update_forces(&mut bodies, 1.0 / 60.0);
+
+        bodies[body].force_accum
+    }
+
+    #[test]
+    fn body_moving_with_the_wind_feels_no_force() {
+        let wind_speed = Vec3::new(5.0, 2.0, 0.0);
+        assert_eq!(aero_force(wind_speed, wind_speed), Vec3::ZERO);
+    }
+
+    #[test]
+    fn tailwind_lessens_the_airflow() {
+        let velocity = Vec3::new(0.0, 4.0, 0.0);
+        let still = aero_force(velocity, Vec3::ZERO);
+        let tailwind = aero_force(velocity, Vec3::new(0.0, 2.0, 0.0));
+
+        assert!(tailwind.magnitude() < still.magnitude());
+    }
+}
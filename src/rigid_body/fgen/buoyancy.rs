@@ -0,0 +1,100 @@
+use crate::{
+    precision::Real,
+    rigid_body::{RigidBodyId, RigidBodySet},
+    Vec3,
+};
+
+use super::ForceGenerator;
+
+/// A force generator to apply a buoyant force to a rigid body.
+///
+/// The force is applied at the centre of buoyancy rather than the
+/// centre of mass, so a floating body that is tipped over will roll
+/// back upright. Sampling several hull points instead of a single
+/// centre of buoyancy gives a stronger righting torque, as the
+/// points on the lower side of the body sink further.
+#[derive(Debug, Clone)]
+pub struct Buoyancy {
+    pub target: RigidBodyId,
+    /// The body-space points the buoyant force is applied at. The
+    /// volume of the body is shared evenly between them.
+    hull_points: Vec<Vec3>,
+    /// The maximum submersion depth of the object before it
+    /// generates its maximum buoyancy force.
+    pub max_depth: Real,
+    /// The volume of the object.
+    pub volume: Real,
+    /// The height of the liquid plane above y=0. The plane is
+    /// assumed to be parallel to the XZ plane.
+    pub liquid_height: Real,
+    /// The density of the liquid. Pure water has a density of
+    /// 1000kg per cubic meter.
+    pub liquid_density: Real,
+}
+
+impl Buoyancy {
+    pub fn new(
+        target: RigidBodyId,
+        centre_of_buoyancy: Vec3,
+        max_depth: Real,
+        volume: Real,
+        liquid_height: Real,
+    ) -> Self {
+        Self {
+            target,
+            hull_points: vec![centre_of_buoyancy],
+            max_depth,
+            volume,
+            liquid_height,
+            liquid_density: 1000.0,
+        }
+    }
+
+    pub fn with_liquid_density(mut self, liquid_density: Real) -> Self {
+        self.liquid_density = liquid_density;
+        self
+    }
+
+    /// Replaces the centre of buoyancy by several body-space points
+    /// on the hull.
+    pub fn with_hull_points(mut self, hull_points: Vec<Vec3>) -> Self {
+        assert!(
+            !hull_points.is_empty(),
+            "A buoyant body needs at least one hull point"
+        );
+        self.hull_points = hull_points;
+        self
+    }
+
+    pub fn hull_points(&self) -> &[Vec3] {
+        &self.hull_points
+    }
+}
+
+impl ForceGenerator for Buoyancy {
+    fn update_forces(&mut self, bodies: &mut RigidBodySet, _duration: Real) {
+        let body = &mut bodies[self.target];
+        let point_volume = self.volume / self.hull_points.len() as Real;
+
+        for &point in &self.hull_points {
+            let depth = body.get_point_in_world_space(point).y;
+
+            // Check if we're out of the water.
+            if depth >= self.liquid_height + self.max_depth {
+                continue;
+            }
+
+            let buoyancy_force = if depth <= self.liquid_height - self.max_depth {
+                // We're at maximum depth.
+                point_volume * self.liquid_density
+            } else {
+                // Otherwise we are partly submerged.
+                let degree_of_submersion =
+                    (self.liquid_height + self.max_depth - depth) / (2.0 * self.max_depth);
+                degree_of_submersion * point_volume * self.liquid_density
+            };
+
+            body.add_force_at_body_point(Vec3::Y * buoyancy_force, point);
+        }
+    }
+}
@@ -0,0 +1,344 @@
+use crate::{
+    particle::{fgen::ParticleForceGenerator, ParticleSet},
+    precision::Real,
+    rigid_body::RigidBodySet,
+    Vec3,
+};
+
+use super::ForceGenerator;
+
+/// A force generator showing a three component explosion effect.
+///
+/// First the objects around the detonation are briefly pulled in
+/// (the implosion), then a shockwave expands outwards from the
+/// detonation, knocking away whatever it passes with a blow that
+/// weakens with distance, and finally a chimney of hot air rises
+/// above the detonation and carries light objects up with it (the
+/// convection).
+///
+/// The explosion acts on every body in the set it is registered
+/// with. It implements both [`ForceGenerator`] and
+/// [`ParticleForceGenerator`], so a clone of it can be registered
+/// for the particles as well. Every phase is disabled until it is
+/// set up with its `with_*` method, and the explosion expires once
+/// all of them are over.
+#[derive(Debug, Clone)]
+pub struct Explosion {
+    /// The location of the detonation of the weapon.
+    pub detonation: Vec3,
+
+    /// The radius up to which objects implode in the first stage
+    /// of the explosion.
+    pub implosion_max_radius: Real,
+    /// The radius within which objects don't feel the implosion
+    /// force. Objects near to the detonation aren't sucked in by
+    /// the air implosion.
+    pub implosion_min_radius: Real,
+    /// The length of time that objects spend imploding before the
+    /// concussion phase kicks in.
+    pub implosion_duration: Real,
+    /// The maximal force that the implosion can apply. This should
+    /// be relatively small to avoid the implosion pulling objects
+    /// through the detonation point and out the other side before
+    /// the concussion wave kicks in.
+    pub implosion_force: Real,
+
+    /// The speed that the shock wave is traveling.
+    pub shockwave_speed: Real,
+    /// The thickness of the shell of pressure in the shock wave.
+    /// Together with the speed and the peak force, it sets how hard
+    /// the wave hits: an object gets the impulse the force would
+    /// give it while the shell passed.
+    pub shockwave_thickness: Real,
+    /// This is the force at the very centre of the shock wave's
+    /// shell, as it sets out from the detonation. Objects that are
+    /// already moving outwards get proportionally less of it.
+    pub peak_concussion_force: Real,
+    /// The length of time that the concussion wave is active. The
+    /// wave weakens as it spreads, until it dies out at the distance
+    /// it reaches in this time.
+    pub concussion_duration: Real,
+
+    /// This is the peak force for stationary objects in the centre
+    /// of the convection chimney.
+    pub peak_convection_force: Real,
+    /// The radius of the chimney cylinder in the xz plane.
+    pub chimney_radius: Real,
+    /// The maximum height of the chimney.
+    pub chimney_height: Real,
+    /// The length of time the convection chimney is active.
+    /// Typically this is the longest effect to be in operation, as
+    /// the heat from the explosion outlives the shock wave and
+    /// implosion itself.
+    pub convection_duration: Real,
+
+    /// Tracks how long the explosion has been in operation.
+    time_passed: Real,
+}
+
+impl Explosion {
+    pub fn new(detonation: Vec3) -> Self {
+        Self {
+            detonation,
+            implosion_max_radius: 0.0,
+            implosion_min_radius: 0.0,
+            implosion_duration: 0.0,
+            implosion_force: 0.0,
+            shockwave_speed: 0.0,
+            shockwave_thickness: 0.0,
+            peak_concussion_force: 0.0,
+            concussion_duration: 0.0,
+            peak_convection_force: 0.0,
+            chimney_radius: 0.0,
+            chimney_height: 0.0,
+            convection_duration: 0.0,
+            time_passed: 0.0,
+        }
+    }
+
+    pub fn with_implosion(
+        mut self,
+        min_radius: Real,
+        max_radius: Real,
+        duration: Real,
+        force: Real,
+    ) -> Self {
+        self.implosion_min_radius = min_radius;
+        self.implosion_max_radius = max_radius;
+        self.implosion_duration = duration;
+        self.implosion_force = force;
+        self
+    }
+
+    pub fn with_shockwave(
+        mut self,
+        speed: Real,
+        thickness: Real,
+        peak_force: Real,
+        duration: Real,
+    ) -> Self {
+        self.shockwave_speed = speed;
+        self.shockwave_thickness = thickness;
+        self.peak_concussion_force = peak_force;
+        self.concussion_duration = duration;
+        self
+    }
+
+    pub fn with_convection(
+        mut self,
+        peak_force: Real,
+        chimney_radius: Real,
+        chimney_height: Real,
+        duration: Real,
+    ) -> Self {
+        self.peak_convection_force = peak_force;
+        self.chimney_radius = chimney_radius;
+        self.chimney_height = chimney_height;
+        self.convection_duration = duration;
+        self
+    }
+
+    /// Returns how long the explosion has been in operation.
+    pub fn time_passed(&self) -> Real {
+        self.time_passed
+    }
+
+    /// Returns whether every phase of the explosion is over.
+    pub fn is_finished(&self) -> bool {
+        let after_implosion = self.time_passed - self.implosion_duration;
+        after_implosion >= self.concussion_duration.max(self.convection_duration)
+    }
+
+    /// Calculates the force the implosion and convection apply to
+    /// an object at the given position, at the current point in
+    /// time. The shock wave is a blow rather than a force; see
+    /// [`Explosion::concussion_impulse_at`].
+    pub fn force_at(&self, position: Vec3) -> Vec3 {
+        let time = self.time_passed;
+        let offset = position - self.detonation;
+        let distance = offset.magnitude();
+
+        if time < self.implosion_duration {
+            // The implosion pulls in everything in a shell around the
+            // detonation.
+            if distance >= self.implosion_min_radius && distance <= self.implosion_max_radius {
+                return offset.normalized() * -self.implosion_force;
+            }
+            return Vec3::ZERO;
+        }
+
+        let after_implosion = time - self.implosion_duration;
+        if after_implosion < self.convection_duration && self.chimney_radius > 0.0 {
+            // The chimney rises straight up from the detonation, and
+            // is strongest along its centre.
+            let height = offset.y;
+            let horizontal = Vec3::new(offset.x, 0.0, offset.z).magnitude();
+            if height >= 0.0 && height <= self.chimney_height && horizontal < self.chimney_radius {
+                let fade = 1.0 - after_implosion / self.convection_duration;
+                return Vec3::Y
+                    * (self.peak_convection_force
+                        * (1.0 - horizontal / self.chimney_radius)
+                        * fade);
+            }
+        }
+
+        Vec3::ZERO
+    }
+
+    /// Calculates the impulse the shock wave gives an object at the
+    /// given position and moving at the given velocity, if the front
+    /// of the wave passes it in the next `duration`. Every object is
+    /// hit once, however long the steps are.
+    pub fn concussion_impulse_at(&self, position: Vec3, velocity: Vec3, duration: Real) -> Vec3 {
+        if self.shockwave_speed <= 0.0 || self.concussion_duration <= 0.0 {
+            return Vec3::ZERO;
+        }
+
+        let start = (self.time_passed - self.implosion_duration).max(0.0);
+        let end =
+            (self.time_passed + duration - self.implosion_duration).min(self.concussion_duration);
+        if end <= start {
+            return Vec3::ZERO;
+        }
+
+        let offset = position - self.detonation;
+        let distance = offset.magnitude();
+        if distance < self.shockwave_speed * start || distance >= self.shockwave_speed * end {
+            return Vec3::ZERO;
+        }
+
+        // The wave spreads its push over more and more of the world,
+        // so it weakens with distance until it dies out.
+        let reach = self.shockwave_speed * self.concussion_duration;
+        let falloff = 1.0 - distance / reach;
+
+        // Objects already moving away with the wave feel less of it.
+        let direction = offset.normalized();
+        let outward_speed = velocity.dot(direction).clamp(0.0, self.shockwave_speed);
+        let relative_speed = 1.0 - outward_speed / self.shockwave_speed;
+
+        // The shell's force rises to its peak and falls again as the
+        // shell passes, which takes thickness / speed.
+        let impulse =
+            self.peak_concussion_force * self.shockwave_thickness / (2.0 * self.shockwave_speed);
+        direction * (impulse * falloff * relative_speed)
+    }
+
+    /// Returns the force that gives an object the explosion's pull
+    /// and push over the next `duration`. The impulse of the shock
+    /// wave is spread over the step, so it is the same whatever the
+    /// length of the step.
+    fn step_force(&self, position: Vec3, velocity: Vec3, duration: Real) -> Vec3 {
+        let mut force = self.force_at(position);
+        if duration > 0.0 {
+            force += self.concussion_impulse_at(position, velocity, duration) / duration;
+        }
+        force
+    }
+
+    fn advance(&mut self, duration: Real) {
+        self.time_passed += duration;
+    }
+}
+
+impl ForceGenerator for Explosion {
+    fn update_forces(&mut self, bodies: &mut RigidBodySet, duration: Real) {
+        if self.is_finished() {
+            return;
+        }
+
+        for body in bodies.bodies_mut() {
+            if body.inverse_mass == 0.0 {
+                continue;
+            }
+
+            // Bodies out of reach are left alone, so they stay asleep.
+            let force = self.step_force(body.position, body.velocity, duration);
+            if force != Vec3::ZERO {
+                body.add_force(force);
+            }
+        }
+
+        self.advance(duration);
+    }
+
+    fn is_expired(&self) -> bool {
+        self.is_finished()
+    }
+}
+
+impl ParticleForceGenerator for Explosion {
+    fn update_forces(&mut self, particles: &mut ParticleSet, duration: Real) {
+        if self.is_finished() {
+            return;
+        }
+
+        for particle in particles.particles_mut() {
+            particle.add_force(self.step_force(particle.position, particle.velocity, duration));
+        }
+
+        self.advance(duration);
+    }
+
+    fn is_expired(&self) -> bool {
+        self.is_finished()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::particle::{
+        fgen::ParticleForceGeneratorSet, Particle, ParticleId, ParticlePhysicsSystem,
+    };
+
+    use super::*;
+
+    /// Sets off a shock wave next to particles at the given distances,
+    /// and returns how fast they are knocked away.
+    fn knock_away(distances: &[Real], duration: Real) -> Vec<Real> {
+        let mut particles = ParticleSet::new();
+        let ids: Vec<ParticleId> = distances
+            .iter()
+            .map(|&distance| {
+                particles.insert(
+                    Particle::new(1.0)
+                        .with_position(Vec3::new(distance, 0.0, 0.0))
+                        .with_damping(1.0),
+                )
+            })
+            .collect();
+
+        let mut generators = ParticleForceGeneratorSet::new();
+        generators.insert(Explosion::new(Vec3::ZERO).with_shockwave(20.0, 2.0, 100.0, 1.0));
+
+        let mut system = ParticlePhysicsSystem::new(1, 1);
+        let steps = (1.0 / duration).round() as usize;
+        for _ in 0..steps {
+            system.start_frame(&mut particles);
+            system.step(&mut particles, &mut generators, duration);
+        }
+
+        ids.iter().map(|&id| particles[id].velocity.x).collect()
+    }
+
+    #[test]
+    fn shock_wave_does_not_depend_on_the_step() {
+        let long = knock_away(&[3.0, 7.0], 1.0 / 30.0);
+        let short = knock_away(&[3.0, 7.0], 1.0 / 240.0);
+
+        for (long, short) in long.into_iter().zip(short) {
+            assert!(long > 0.0);
+            assert!((long - short).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn shock_wave_weakens_with_distance() {
+        let speeds = knock_away(&[2.0, 8.0, 25.0], 1.0 / 60.0);
+
+        assert!(speeds[0] > speeds[1]);
+        assert!(speeds[1] > 0.0);
+        // The wave dies out before it reaches the last one.
+        assert_eq!(speeds[2], 0.0);
+    }
+}
@@ -0,0 +1,179 @@
+use crate::{
+    fgen_common::drag_force,
+    precision::Real,
+    rigid_body::{RigidBodyId, RigidBodySet},
+    Vec3,
+};
+
+use super::ForceGenerator;
+
+/// A force generator that applies a uniform gravitational force to
+/// a set of rigid bodies.
+#[derive(Debug, Clone)]
+pub struct Gravity {
+    pub targets: Vec<RigidBodyId>,
+    /// Holds the acceleration due to gravity.
+    pub gravity: Vec3,
+}
+
+impl Gravity {
+    pub fn new(targets: Vec<RigidBodyId>, gravity: Vec3) -> Self {
+        Self { targets, gravity }
+    }
+}
+
+impl ForceGenerator for Gravity {
+    fn update_forces(&mut self, bodies: &mut RigidBodySet, _duration: Real) {
+        for &target in &self.targets {
+            // Bodies removed from the set are skipped.
+            let Some(body) = bodies.get_mut(target) else {
+                continue;
+            };
+
+            // Check that we do not have infinite mass.
+            if body.inverse_mass == 0.0 {
+                continue;
+            }
+
+            body.add_force(self.gravity * body.mass());
+        }
+    }
+}
+
+/// A force generator that pulls a set of rigid bodies towards a
+/// point, with a force that falls off with the square of the
+/// distance.
+#[derive(Debug, Clone)]
+pub struct GravityWell {
+    pub targets: Vec<RigidBodyId>,
+    /// Holds the position of the centre of the well.
+    pub centre: Vec3,
+    /// Holds the strength of the well: the acceleration it gives a
+    /// body one unit away from its centre.
+    pub strength: Real,
+    /// Holds the distance below which the force stops growing, so
+    /// bodies passing close to the centre aren't flung away.
+    pub min_distance: Real,
+}
+
+impl GravityWell {
+    pub fn new(targets: Vec<RigidBodyId>, centre: Vec3, strength: Real) -> Self {
+        Self {
+            targets,
+            centre,
+            strength,
+            min_distance: 0.1,
+        }
+    }
+
+    pub fn with_min_distance(mut self, min_distance: Real) -> Self {
+        self.min_distance = min_distance;
+        self
+    }
+}
+
+impl ForceGenerator for GravityWell {
+    fn update_forces(&mut self, bodies: &mut RigidBodySet, _duration: Real) {
+        for &target in &self.targets {
+            let Some(body) = bodies.get_mut(target) else {
+                continue;
+            };
+            if body.inverse_mass == 0.0 {
+                continue;
+            }
+
+            let offset = self.centre - body.position;
+            let squared_distance = offset
+                .squared_magnitude()
+                .max(self.min_distance * self.min_distance);
+
+            let acceleration = self.strength / squared_distance;
+            body.add_force(offset.normalized() * acceleration * body.mass());
+        }
+    }
+}
+
+/// A force generator that applies a drag force to a set of rigid
+/// bodies. The magnitude of the force is `k1 * v + k2 * v^2`, where
+/// `v` is the speed of the body.
+#[derive(Debug, Clone)]
+pub struct Drag {
+    pub targets: Vec<RigidBodyId>,
+    /// Holds the velocity drag coefficient.
+    pub k1: Real,
+    /// Holds the velocity squared drag coefficient.
+    pub k2: Real,
+}
+
+impl Drag {
+    pub fn new(targets: Vec<RigidBodyId>, k1: Real, k2: Real) -> Self {
+        Self { targets, k1, k2 }
+    }
+}
+
+impl ForceGenerator for Drag {
+    fn update_forces(&mut self, bodies: &mut RigidBodySet, _duration: Real) {
+        for &target in &self.targets {
+            let Some(body) = bodies.get_mut(target) else {
+                continue;
+            };
+            body.add_force(drag_force(body.velocity, self.k1, self.k2));
+        }
+    }
+}
+
+/// A force generator that applies drag against moving air to a set
+/// of rigid bodies, so they get carried along by the wind.
+#[derive(Debug, Clone)]
+pub struct Wind {
+    pub targets: Vec<RigidBodyId>,
+    /// Holds the velocity of the air.
+    pub wind_velocity: Vec3,
+    /// Holds the velocity drag coefficient.
+    pub k1: Real,
+    /// Holds the velocity squared drag coefficient.
+    pub k2: Real,
+}
+
+impl Wind {
+    pub fn new(targets: Vec<RigidBodyId>, wind_velocity: Vec3, k1: Real, k2: Real) -> Self {
+        Self {
+            targets,
+            wind_velocity,
+            k1,
+            k2,
+        }
+    }
+}
+
+impl ForceGenerator for Wind {
+    fn update_forces(&mut self, bodies: &mut RigidBodySet, _duration: Real) {
+        for &target in &self.targets {
+            let Some(body) = bodies.get_mut(target) else {
+                continue;
+            };
+            let relative_velocity = body.velocity - self.wind_velocity;
+            body.add_force(drag_force(relative_velocity, self.k1, self.k2));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rigid_body::RigidBody;
+
+    use super::*;
+
+    #[test]
+    fn removed_targets_are_skipped() {
+        let mut bodies = RigidBodySet::new();
+        let removed = bodies.insert(RigidBody::new(1.0));
+        let kept = bodies.insert(RigidBody::new(1.0));
+        bodies.remove(removed);
+
+        let gravity = Vec3::new(0.0, -10.0, 0.0);
+        Gravity::new(vec![removed, kept], gravity).update_forces(&mut bodies, 1.0 / 60.0);
+
+        assert_eq!(bodies[kept].force_accum, gravity);
+    }
+}
@@ -1,3 +1,130 @@
+mod aero;
+mod buoyancy;
+mod explosion;
+mod fields;
 mod springs;
+mod timed;
 
-pub use springs::{AnchoredSpring, Spring};
+pub use aero::{Aero, AeroControl, AngledAero};
+pub use buoyancy::Buoyancy;
+pub use explosion::Explosion;
+pub use fields::{Drag, Gravity, GravityWell, Wind};
+pub use springs::{AnchoredSpring, Spring, StiffSpring};
+pub use timed::Timed;
+
+use derive_more::{From, Index, IndexMut, IntoIterator};
+use downcast_rs::{impl_downcast, Downcast};
+use slotmap::{new_key_type, SlotMap};
+
+use crate::precision::Real;
+
+use super::RigidBodySet;
+
+pub trait ForceGenerator: Downcast {
+    fn update_forces(&mut self, bodies: &mut RigidBodySet, duration: Real);
+
+    /// Broken generators no longer apply forces.
+    fn is_broken(&self) -> bool {
+        false
+    }
+
+    /// Expired generators are removed from their set once they've
+    /// applied their forces.
+    fn is_expired(&self) -> bool {
+        false
+    }
+}
+
+new_key_type! {
+    pub struct ForceGeneratorId;
+}
+
+#[derive(Default, IntoIterator, Index, IndexMut, From)]
+pub struct ForceGeneratorSet {
+    inner: SlotMap<ForceGeneratorId, Box<dyn ForceGenerator>>,
+}
+
+impl_downcast!(ForceGenerator);
+
+impl ForceGeneratorSet {
+    pub fn new() -> Self {
+        Self {
+            inner: SlotMap::with_key(),
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            inner: SlotMap::with_capacity_and_key(capacity),
+        }
+    }
+
+    /// Applies the forces of every generator that isn't broken, and
+    /// then removes the generators that have expired. Returns the
+    /// generators that broke during this update.
+    pub fn update_forces(
+        &mut self,
+        bodies: &mut RigidBodySet,
+        duration: Real,
+    ) -> Vec<ForceGeneratorId> {
+        let mut broken = Vec::new();
+        for (id, generator) in self.inner.iter_mut() {
+            if generator.is_broken() {
+                continue;
+            }
+
+            generator.update_forces(bodies, duration);
+            if generator.is_broken() {
+                broken.push(id);
+            }
+        }
+
+        self.inner.retain(|_, generator| !generator.is_expired());
+
+        broken
+    }
+
+    pub fn insert<F: ForceGenerator + 'static>(&mut self, value: F) -> ForceGeneratorId {
+        self.inner.insert(Box::new(value))
+    }
+
+    pub fn delete(&mut self, key: ForceGeneratorId) {
+        self.inner.remove(key);
+    }
+
+    pub fn remove<F: ForceGenerator>(&mut self, key: ForceGeneratorId) -> Option<Box<F>> {
+        self.inner.remove(key)?.downcast().ok()
+    }
+
+    pub fn clear(&mut self) {
+        self.inner.clear()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn get<F: ForceGenerator>(&self, key: ForceGeneratorId) -> Option<&F> {
+        self.inner.get(key)?.downcast_ref()
+    }
+
+    pub fn get_mut<F: ForceGenerator>(&mut self, key: ForceGeneratorId) -> Option<&mut F> {
+        self.inner.get_mut(key)?.downcast_mut()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    pub fn contains(&self, key: ForceGeneratorId) -> bool {
+        self.inner.contains_key(key)
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.inner.reserve(additional)
+    }
+}
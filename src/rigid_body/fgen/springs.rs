@@ -1,9 +1,12 @@
 use crate::{
+    fgen_common::{exceeds_break_force, stiff_spring_target},
     precision::Real,
     rigid_body::{RigidBodyId, RigidBodySet},
     Vec3,
 };
 
+use super::ForceGenerator;
+
 #[derive(Debug, Clone)]
 pub struct Spring {
     pub body_a: RigidBodyId,
@@ -12,23 +15,77 @@ pub struct Spring {
     pub connection_point_b: Vec3,
     pub spring_constant: Real,
     pub rest_length: Real,
+    /// Holds the damping coefficient, which opposes the speed at
+    /// which the spring stretches or compresses.
+    pub damping: Real,
+    /// Holds the force above which the spring snaps. Unbreakable
+    /// springs have no break force.
+    pub break_force: Option<Real>,
+    broken: bool,
 }
 
 impl Spring {
-    pub fn add_forces(&self, bodies: &mut RigidBodySet) {
+    pub fn new(
+        body_a: RigidBodyId,
+        body_b: RigidBodyId,
+        connection_point_a: Vec3,
+        connection_point_b: Vec3,
+        spring_constant: Real,
+        rest_length: Real,
+    ) -> Self {
+        Self {
+            body_a,
+            body_b,
+            connection_point_a,
+            connection_point_b,
+            spring_constant,
+            rest_length,
+            damping: 0.0,
+            break_force: None,
+            broken: false,
+        }
+    }
+
+    pub fn with_damping(mut self, damping: Real) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    pub fn with_break_force(mut self, break_force: Real) -> Self {
+        self.break_force = Some(break_force);
+        self
+    }
+}
+
+impl ForceGenerator for Spring {
+    fn update_forces(&mut self, bodies: &mut RigidBodySet, _duration: Real) {
         let [body_a, body_b] = bodies.get_disjoint_mut([self.body_a, self.body_b]).unwrap();
         let connect_point_a_ws = body_a.get_point_in_world_space(self.connection_point_a);
         let connect_point_b_ws = body_b.get_point_in_world_space(self.connection_point_b);
 
-        let delta = connect_point_b_ws - connect_point_a_ws;
+        let delta = connect_point_a_ws - connect_point_b_ws;
         let direction = delta.normalized();
+        let speed = (body_a.velocity_at_point(connect_point_a_ws)
+            - body_b.velocity_at_point(connect_point_b_ws))
+        .dot(direction);
+
+        // Hook's law, plus damping
+        let force = (-self.spring_constant * (delta.magnitude() - self.rest_length)
+            - self.damping * speed)
+            * direction;
 
-        // Hook's law
-        let force = self.spring_constant * (delta.magnitude() - self.rest_length) * -direction;
+        if exceeds_break_force(force, self.break_force) {
+            self.broken = true;
+            return;
+        }
 
         body_a.add_force_at_point(force, connect_point_a_ws);
         body_b.add_force_at_point(-force, connect_point_b_ws);
     }
+
+    fn is_broken(&self) -> bool {
+        self.broken
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -38,19 +95,139 @@ pub struct AnchoredSpring {
     pub connection_point: Vec3,
     pub spring_constant: Real,
     pub rest_length: Real,
+    /// Holds the damping coefficient, which opposes the speed at
+    /// which the spring stretches or compresses.
+    pub damping: Real,
+    /// Holds the force above which the spring snaps. Unbreakable
+    /// springs have no break force.
+    pub break_force: Option<Real>,
+    broken: bool,
 }
 
 impl AnchoredSpring {
-    pub fn add_forces(&self, bodies: &mut RigidBodySet) {
+    pub fn new(
+        target: RigidBodyId,
+        anchor: Vec3,
+        connection_point: Vec3,
+        spring_constant: Real,
+        rest_length: Real,
+    ) -> Self {
+        Self {
+            target,
+            anchor,
+            connection_point,
+            spring_constant,
+            rest_length,
+            damping: 0.0,
+            break_force: None,
+            broken: false,
+        }
+    }
+
+    pub fn with_damping(mut self, damping: Real) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    pub fn with_break_force(mut self, break_force: Real) -> Self {
+        self.break_force = Some(break_force);
+        self
+    }
+}
+
+impl ForceGenerator for AnchoredSpring {
+    fn update_forces(&mut self, bodies: &mut RigidBodySet, _duration: Real) {
         let body = &mut bodies[self.target];
         let connect_point_ws = body.get_point_in_world_space(self.connection_point);
 
         let delta = connect_point_ws - self.anchor;
+        let direction = delta.normalized();
+        let speed = body.velocity_at_point(connect_point_ws).dot(direction);
 
-        // Hook's law
-        let force =
-            self.spring_constant * (delta.magnitude() - self.rest_length) * -delta.normalized();
+        // Hook's law, plus damping
+        let force = (-self.spring_constant * (delta.magnitude() - self.rest_length)
+            - self.damping * speed)
+            * direction;
+
+        if exceeds_break_force(force, self.break_force) {
+            self.broken = true;
+            return;
+        }
 
         body.add_force_at_point(force, connect_point_ws);
     }
+
+    fn is_broken(&self) -> bool {
+        self.broken
+    }
+}
+
+/// A force generator that fakes a stiff spring force between a
+/// point on a rigid body and a fixed point in space.
+///
+/// Like [`ParticleStiffSpring`](crate::particle::fgen::ParticleStiffSpring),
+/// it works out where the damped harmonic motion of the connection
+/// point takes it by the end of the frame, treating the body as a
+/// point mass, and applies the force that gets it there. The spring
+/// has no rest length.
+#[derive(Debug, Clone)]
+pub struct StiffSpring {
+    pub target: RigidBodyId,
+    pub anchor: Vec3,
+    pub connection_point: Vec3,
+    /// Holds the spring constant, relative to the mass of the body.
+    pub spring_constant: Real,
+    /// Holds the damping on the oscillation of the spring. It has
+    /// to be below `2 * spring_constant.sqrt()`, as the fake spring
+    /// only handles underdamped motion.
+    pub damping: Real,
+}
+
+impl StiffSpring {
+    pub fn new(
+        target: RigidBodyId,
+        anchor: Vec3,
+        connection_point: Vec3,
+        spring_constant: Real,
+        damping: Real,
+    ) -> Self {
+        Self {
+            target,
+            anchor,
+            connection_point,
+            spring_constant,
+            damping,
+        }
+    }
+}
+
+impl ForceGenerator for StiffSpring {
+    fn update_forces(&mut self, bodies: &mut RigidBodySet, duration: Real) {
+        let body = &mut bodies[self.target];
+
+        // Check that we do not have infinite mass.
+        if body.inverse_mass == 0.0 {
+            return;
+        }
+
+        let connect_point_ws = body.get_point_in_world_space(self.connection_point);
+        let offset = connect_point_ws - self.anchor;
+        let velocity = body.velocity_at_point(connect_point_ws);
+        let Some(target) = stiff_spring_target(
+            offset,
+            velocity,
+            self.spring_constant,
+            self.damping,
+            duration,
+        ) else {
+            return;
+        };
+
+        // Calculate the acceleration that takes the point to the
+        // target by the end of the frame.
+        let acceleration =
+            (target - offset) * (duration * duration).recip() - velocity * duration.recip();
+
+        body.add_force_at_point(acceleration * body.mass(), connect_point_ws);
+    }
 }
@@ -0,0 +1,43 @@
+use crate::{precision::Real, rigid_body::RigidBodySet};
+
+use super::ForceGenerator;
+
+/// Wraps a force generator so that it only applies its forces for a
+/// limited time, after which it expires and is removed from its set.
+#[derive(Debug, Clone)]
+pub struct Timed<F> {
+    pub generator: F,
+    /// Holds how long the generator applies its forces for.
+    pub lifetime: Real,
+    time_passed: Real,
+}
+
+impl<F: ForceGenerator> Timed<F> {
+    pub fn new(generator: F, lifetime: Real) -> Self {
+        Self {
+            generator,
+            lifetime,
+            time_passed: 0.0,
+        }
+    }
+
+    /// Returns how long the generator has been applying its forces.
+    pub fn time_passed(&self) -> Real {
+        self.time_passed
+    }
+}
+
+impl<F: ForceGenerator> ForceGenerator for Timed<F> {
+    fn update_forces(&mut self, bodies: &mut RigidBodySet, duration: Real) {
+        self.generator.update_forces(bodies, duration);
+        self.time_passed += duration;
+    }
+
+    fn is_broken(&self) -> bool {
+        self.generator.is_broken()
+    }
+
+    fn is_expired(&self) -> bool {
+        self.time_passed >= self.lifetime || self.generator.is_expired()
+    }
+}
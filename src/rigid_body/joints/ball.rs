@@ -0,0 +1,132 @@
+use crate::{
+    precision::Real,
+    rigid_body::{RigidBodyId, RigidBodySet},
+    Vec3,
+};
+
+use super::{
+    constraint::{BodyPair, JointLoad},
+    Joint,
+};
+
+/// Joins two rigid bodies together at a point, leaving them free
+/// to rotate around it, like a ball-and-socket.
+#[derive(Debug, Clone)]
+pub struct BallJoint {
+    pub body_a: RigidBodyId,
+    /// When there is no second body, body A is joined to a fixed
+    /// point in the world, and connection point B is in world space.
+    pub body_b: Option<RigidBodyId>,
+    /// Holds the location of the connection on body A, in body space.
+    pub connection_point_a: Vec3,
+    /// Holds the location of the connection on body B, in body space.
+    pub connection_point_b: Vec3,
+    /// Holds the maximum displacement at the joint before the
+    /// joint is considered to be violated. This is normally a
+    /// small, epsilon value. It can be larger, however, in which
+    /// case the joint will behave as if an inelastic cable joined
+    /// the bodies at their joint locations.
+    pub error: Real,
+    /// Holds the force above which the joint breaks. Unbreakable
+    /// joints have no break force.
+    pub break_force: Option<Real>,
+    load: JointLoad,
+}
+
+impl BallJoint {
+    pub fn new(
+        body_a: RigidBodyId,
+        body_b: Option<RigidBodyId>,
+        connection_point_a: Vec3,
+        connection_point_b: Vec3,
+        error: Real,
+    ) -> Self {
+        Self {
+            body_a,
+            body_b,
+            connection_point_a,
+            connection_point_b,
+            error,
+            break_force: None,
+            load: JointLoad::default(),
+        }
+    }
+
+    pub fn with_break_force(mut self, break_force: Real) -> Self {
+        self.break_force = Some(break_force);
+        self
+    }
+}
+
+impl Joint for BallJoint {
+    fn prepare(&mut self, _bodies: &RigidBodySet, _duration: Real) {
+        self.load.reset();
+    }
+
+    fn resolve_velocity(&mut self, bodies: &mut RigidBodySet, _duration: Real) {
+        let mut pair = BodyPair::new(bodies, self.body_a, self.body_b);
+        let point_a = pair.a.get_point_in_world_space(self.connection_point_a);
+        let point_b = pair.point_in_world_space_b(self.connection_point_b);
+
+        let impulse = pair.resolve_point_velocity(point_a, point_b, self.error);
+        self.load.add_impulse(impulse);
+    }
+
+    fn resolve_position(&mut self, bodies: &mut RigidBodySet, _duration: Real) {
+        let mut pair = BodyPair::new(bodies, self.body_a, self.body_b);
+        let point_a = pair.a.get_point_in_world_space(self.connection_point_a);
+        let point_b = pair.point_in_world_space_b(self.connection_point_b);
+
+        pair.resolve_point_position(point_a, point_b, self.error);
+    }
+
+    fn check_break(&mut self, duration: Real) -> bool {
+        self.load.check_break(self.break_force, None, duration)
+    }
+
+    fn is_broken(&self) -> bool {
+        self.load.is_broken()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rigid_body::{fgen::ForceGeneratorSet, joints::JointSet, PhysicsSystem, RigidBody};
+
+    use super::*;
+
+    #[test]
+    fn joint_without_second_body_hangs_from_the_world() {
+        let mut bodies = RigidBodySet::new();
+        let body = bodies.insert(
+            RigidBody::new(1.0)
+                .with_position(Vec3::new(1.0, 0.0, 0.0))
+                .with_acceleration(Vec3::new(0.0, -9.81, 0.0)),
+        );
+
+        let anchor = Vec3::new(0.0, 0.0, 0.0);
+        let mut joints = JointSet::new();
+        joints.insert(BallJoint::new(
+            body,
+            None,
+            Vec3::new(-1.0, 0.0, 0.0),
+            anchor,
+            0.0,
+        ));
+
+        let mut system = PhysicsSystem::new();
+        let mut generators = ForceGeneratorSet::new();
+        for _ in 0..120 {
+            system.start_frame(&mut bodies);
+            system.step_with_joints(&mut bodies, &mut generators, &mut joints, 1.0 / 60.0);
+        }
+
+        let connection = bodies[body].get_point_in_world_space(Vec3::new(-1.0, 0.0, 0.0));
+        assert!((connection - anchor).magnitude() < 0.01);
+
+        // The body swings around the anchor, rather than falling.
+        let position = bodies[body].position;
+        assert!(((position - anchor).magnitude() - 1.0).abs() < 0.01);
+        assert!(position.y < -0.1);
+    }
+}
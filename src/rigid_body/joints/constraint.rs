@@ -0,0 +1,393 @@
+use crate::{
+    math::make_orthonormal_basis,
+    precision::Real,
+    rigid_body::{RigidBody, RigidBodyId, RigidBodySet},
+    Mat3, Mat4, Quat, Vec3,
+};
+
+/// The bodies taking part in a joint. When there is no second
+/// body, the joint is attached to the world, which never moves.
+///
+/// Every relative quantity is measured as body B relative to body A,
+/// and every impulse is applied to body B, with its opposite applied
+/// to body A.
+pub(super) struct BodyPair<'a> {
+    pub a: &'a mut RigidBody,
+    pub b: Option<&'a mut RigidBody>,
+}
+
+impl<'a> BodyPair<'a> {
+    pub fn new(
+        bodies: &'a mut RigidBodySet,
+        body_a: RigidBodyId,
+        body_b: Option<RigidBodyId>,
+    ) -> Self {
+        match body_b {
+            Some(body_b) => {
+                let [a, b] = bodies.get_disjoint_mut([body_a, body_b]).unwrap();
+                Self { a, b: Some(b) }
+            }
+            None => Self {
+                a: &mut bodies[body_a],
+                b: None,
+            },
+        }
+    }
+
+    /// Converts a point given in the space of body B into world space.
+    pub fn point_in_world_space_b(&self, point: Vec3) -> Vec3 {
+        match self.b.as_deref() {
+            Some(b) => b.get_point_in_world_space(point),
+            None => point,
+        }
+    }
+
+    /// Converts a direction given in the space of body B into world space.
+    pub fn direction_in_world_space_b(&self, direction: Vec3) -> Vec3 {
+        match self.b.as_deref() {
+            Some(b) => b.transform_matrix().transform_direction(direction),
+            None => direction,
+        }
+    }
+
+    pub fn orientation_b(&self) -> Quat {
+        self.b.as_deref().map_or(Quat::IDENTITY, |b| b.orientation)
+    }
+
+    /// Removes the relative velocity between the two given points. When
+    /// the points are allowed to be up to `error` apart, only the velocity
+    /// that would separate them further is removed once they reach that
+    /// distance. Returns the impulse applied.
+    pub fn resolve_point_velocity(&mut self, point_a: Vec3, point_b: Vec3, error: Real) -> Vec3 {
+        let relative_velocity = self.relative_point_velocity(point_a, point_b);
+        let inverse_mass = self.point_inverse_mass(point_a, point_b);
+
+        let impulse = if error > 0.0 {
+            let delta = point_b - point_a;
+            if delta.magnitude() < error {
+                return Vec3::ZERO;
+            }
+
+            let normal = delta.normalized();
+            let separating_velocity = relative_velocity.dot(normal);
+            let normal_inverse_mass = inverse_mass.transform(normal).dot(normal);
+            if separating_velocity <= 0.0 || normal_inverse_mass <= 0.0 {
+                return Vec3::ZERO;
+            }
+
+            normal * (-separating_velocity / normal_inverse_mass)
+        } else {
+            if inverse_mass.determinant().abs() <= Real::EPSILON {
+                return Vec3::ZERO;
+            }
+
+            inverse_mass.inverse().transform(-relative_velocity)
+        };
+
+        self.add_impulse(impulse, point_a, point_b);
+        impulse
+    }
+
+    /// Moves the bodies so that the given points are no further than
+    /// `error` apart.
+    pub fn resolve_point_position(&mut self, point_a: Vec3, point_b: Vec3, error: Real) {
+        let delta = point_b - point_a;
+        let distance = delta.magnitude();
+        if distance <= error {
+            return;
+        }
+
+        let correction = delta * ((distance - error) / distance);
+        let inverse_mass = self.point_inverse_mass(point_a, point_b);
+        if inverse_mass.determinant().abs() <= Real::EPSILON {
+            return;
+        }
+
+        let impulse = inverse_mass.inverse().transform(-correction);
+        self.add_position_impulse(impulse, point_a, point_b);
+    }
+
+    /// Changes the relative velocity of the given points along the given
+    /// direction to the target. Returns the magnitude of the impulse applied.
+    pub fn resolve_velocity_along(
+        &mut self,
+        point_a: Vec3,
+        point_b: Vec3,
+        direction: Vec3,
+        target: Real,
+    ) -> Real {
+        let speed = self
+            .relative_point_velocity(point_a, point_b)
+            .dot(direction);
+        let inverse_mass = self
+            .point_inverse_mass(point_a, point_b)
+            .transform(direction)
+            .dot(direction);
+        if inverse_mass <= 0.0 {
+            return 0.0;
+        }
+
+        let impulse = (target - speed) / inverse_mass;
+        self.add_impulse(direction * impulse, point_a, point_b);
+        impulse
+    }
+
+    /// Moves the bodies so that the given points move the given distance
+    /// relative to each other along the given direction.
+    pub fn translate_along(
+        &mut self,
+        point_a: Vec3,
+        point_b: Vec3,
+        direction: Vec3,
+        distance: Real,
+    ) {
+        let inverse_mass = self
+            .point_inverse_mass(point_a, point_b)
+            .transform(direction)
+            .dot(direction);
+        if inverse_mass <= 0.0 {
+            return;
+        }
+
+        self.add_position_impulse(direction * (distance / inverse_mass), point_a, point_b);
+    }
+
+    /// Returns the velocity of the given point on body B relative to the
+    /// given point on body A.
+    pub fn relative_point_velocity(&self, point_a: Vec3, point_b: Vec3) -> Vec3 {
+        let velocity_b = self
+            .b
+            .as_deref()
+            .map_or(Vec3::ZERO, |b| b.velocity_at_point(point_b));
+
+        velocity_b - self.a.velocity_at_point(point_a)
+    }
+
+    /// Changes the relative angular velocity around the given axis to the
+    /// target. Returns the magnitude of the angular impulse applied.
+    pub fn resolve_angular_velocity_along(&mut self, axis: Vec3, target: Real) -> Real {
+        let speed = self.relative_angular_velocity().dot(axis);
+        let inverse_mass = self.angular_inverse_mass_along(axis);
+        if inverse_mass <= 0.0 {
+            return 0.0;
+        }
+
+        let impulse = (target - speed) / inverse_mass;
+        self.add_angular_impulse(axis * impulse);
+        impulse
+    }
+
+    /// Rotates the bodies so that body B turns by the given angle around
+    /// the given axis relative to body A.
+    pub fn rotate_along(&mut self, axis: Vec3, angle: Real) {
+        let inverse_mass = self.angular_inverse_mass_along(axis);
+        if inverse_mass <= 0.0 {
+            return;
+        }
+
+        self.add_angular_position_impulse(axis * (angle / inverse_mass));
+    }
+
+    /// Removes all relative angular velocity between the bodies. Returns
+    /// the angular impulse applied.
+    pub fn resolve_rotation_lock_velocity(&mut self) -> Vec3 {
+        let inverse_mass = self.angular_inverse_mass();
+        if inverse_mass.determinant().abs() <= Real::EPSILON {
+            return Vec3::ZERO;
+        }
+
+        let impulse = inverse_mass
+            .inverse()
+            .transform(-self.relative_angular_velocity());
+        self.add_angular_impulse(impulse);
+        impulse
+    }
+
+    /// Rotates the bodies so that body B has the given orientation
+    /// relative to body A.
+    pub fn resolve_rotation_lock_position(&mut self, rest_orientation: Quat) {
+        let mut error =
+            self.a.orientation * rest_orientation * self.orientation_b().normalized().inverse();
+        if error.r < 0.0 {
+            error = Quat::from_rijk(-error.r, -error.i, -error.j, -error.k);
+        }
+
+        let error = Vec3::new(error.i, error.j, error.k) * 2.0;
+        let inverse_mass = self.angular_inverse_mass();
+        if inverse_mass.determinant().abs() <= Real::EPSILON {
+            return;
+        }
+
+        self.add_angular_position_impulse(inverse_mass.inverse().transform(error));
+    }
+
+    pub fn relative_angular_velocity(&self) -> Vec3 {
+        let angular_velocity_b = self.b.as_deref().map_or(Vec3::ZERO, |b| b.angular_velocity);
+
+        angular_velocity_b - self.a.angular_velocity
+    }
+
+    /// Calculates the matrix that converts an impulse applied at the given
+    /// points into the change in their relative velocity.
+    fn point_inverse_mass(&self, point_a: Vec3, point_b: Vec3) -> Mat3 {
+        let inverse_mass_a = point_inverse_mass(self.a, point_a);
+        match self.b.as_deref() {
+            Some(b) => inverse_mass_a + point_inverse_mass(b, point_b),
+            None => inverse_mass_a,
+        }
+    }
+
+    /// Returns how much the relative angular velocity around the given
+    /// axis changes for each unit of angular impulse around it.
+    pub fn angular_inverse_mass_along(&self, axis: Vec3) -> Real {
+        self.angular_inverse_mass().transform(axis).dot(axis)
+    }
+
+    /// Calculates the matrix that converts an angular impulse into the
+    /// change in relative angular velocity.
+    fn angular_inverse_mass(&self) -> Mat3 {
+        let inverse_mass_a = self.a.inverse_inertia_tensor_world();
+        match self.b.as_deref() {
+            Some(b) => inverse_mass_a + b.inverse_inertia_tensor_world(),
+            None => inverse_mass_a,
+        }
+    }
+
+    fn add_impulse(&mut self, impulse: Vec3, point_a: Vec3, point_b: Vec3) {
+        self.a.add_impulse_at_point(-impulse, point_a);
+        if let Some(b) = self.b.as_deref_mut() {
+            b.add_impulse_at_point(impulse, point_b);
+        }
+    }
+
+    pub fn add_angular_impulse(&mut self, impulse: Vec3) {
+        self.a.add_angular_impulse(-impulse);
+        if let Some(b) = self.b.as_deref_mut() {
+            b.add_angular_impulse(impulse);
+        }
+    }
+
+    fn add_position_impulse(&mut self, impulse: Vec3, point_a: Vec3, point_b: Vec3) {
+        add_position_impulse_at_point(self.a, -impulse, point_a);
+        if let Some(b) = self.b.as_deref_mut() {
+            add_position_impulse_at_point(b, impulse, point_b);
+        }
+    }
+
+    fn add_angular_position_impulse(&mut self, impulse: Vec3) {
+        rotate_by(
+            self.a,
+            self.a.inverse_inertia_tensor_world().transform(-impulse),
+        );
+        if let Some(b) = self.b.as_deref_mut() {
+            rotate_by(b, b.inverse_inertia_tensor_world().transform(impulse));
+        }
+    }
+}
+
+/// Tracks the load a joint carries during a step, so that it can
+/// break when it is more than the joint can take.
+#[derive(Debug, Clone, Default)]
+pub(super) struct JointLoad {
+    impulse: Vec3,
+    angular_impulse: Vec3,
+    broken: bool,
+}
+
+impl JointLoad {
+    pub fn reset(&mut self) {
+        self.impulse = Vec3::ZERO;
+        self.angular_impulse = Vec3::ZERO;
+    }
+
+    pub fn add_impulse(&mut self, impulse: Vec3) {
+        self.impulse += impulse;
+    }
+
+    pub fn add_angular_impulse(&mut self, impulse: Vec3) {
+        self.angular_impulse += impulse;
+    }
+
+    /// Breaks the joint if the average force or torque it applied
+    /// during the step was above the given ones. Returns whether it
+    /// is broken.
+    pub fn check_break(
+        &mut self,
+        break_force: Option<Real>,
+        break_torque: Option<Real>,
+        duration: Real,
+    ) -> bool {
+        if let Some(break_force) = break_force {
+            if self.impulse.magnitude() > break_force * duration {
+                self.broken = true;
+            }
+        }
+
+        if let Some(break_torque) = break_torque {
+            if self.angular_impulse.magnitude() > break_torque * duration {
+                self.broken = true;
+            }
+        }
+
+        self.broken
+    }
+
+    pub fn is_broken(&self) -> bool {
+        self.broken
+    }
+}
+
+/// Returns two unit vectors perpendicular to the given axis and to
+/// each other.
+pub(super) fn perpendicular_axes(axis: Vec3) -> (Vec3, Vec3) {
+    let other = if axis.x.abs() < 0.57 {
+        Vec3::X
+    } else {
+        Vec3::Y
+    };
+    let (_, b, c) = make_orthonormal_basis(axis, other);
+    (b, c)
+}
+
+/// Returns the transform of the body from its current position and
+/// orientation, whether or not its derived data has been updated.
+pub(super) fn body_transform(body: Option<&RigidBody>) -> Mat4 {
+    match body {
+        Some(body) => {
+            Mat4::from_orientation_and_position(body.orientation.normalized(), body.position)
+        }
+        None => Mat4::IDENTITY,
+    }
+}
+
+/// Returns the orientation of body B relative to body A.
+pub(super) fn relative_orientation(body_a: &RigidBody, body_b: Option<&RigidBody>) -> Quat {
+    let orientation_b = body_b.map_or(Quat::IDENTITY, |b| b.orientation.normalized());
+    body_a.orientation.normalized().inverse() * orientation_b
+}
+
+/// Calculates the matrix that converts an impulse applied at the
+/// given point on the body into the change in velocity of that
+/// point. The point is given in world space.
+fn point_inverse_mass(body: &RigidBody, point: Vec3) -> Mat3 {
+    let arm = Mat3::skew_symmetric(point - body.position);
+
+    Mat3::IDENTITY * body.effective_inverse_mass() - arm * body.inverse_inertia_tensor_world() * arm
+}
+
+/// Applies a position "impulse" at the given point on the body, moving
+/// and rotating it directly instead of changing its velocity.
+fn add_position_impulse_at_point(body: &mut RigidBody, impulse: Vec3, point: Vec3) {
+    let arm = point - body.position;
+    let rotation = body
+        .inverse_inertia_tensor_world()
+        .transform(arm.cross(impulse));
+
+    body.position += impulse * body.effective_inverse_mass();
+    rotate_by(body, rotation);
+}
+
+fn rotate_by(body: &mut RigidBody, rotation: Vec3) {
+    body.orientation = body.orientation.add_scaled_vector(rotation, 1.0);
+    body.update_derived_data();
+}
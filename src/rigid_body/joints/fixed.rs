@@ -0,0 +1,101 @@
+use crate::{
+    precision::Real,
+    rigid_body::{RigidBodyId, RigidBodySet},
+    Quat, Vec3,
+};
+
+use super::{
+    constraint::{body_transform, relative_orientation, BodyPair, JointLoad},
+    Joint,
+};
+
+/// Welds two rigid bodies together, so that they move as if they
+/// were a single body.
+#[derive(Debug, Clone)]
+pub struct FixedJoint {
+    pub body_a: RigidBodyId,
+    /// When there is no second body, body A is welded to the world,
+    /// and everything given for body B is in world space.
+    pub body_b: Option<RigidBodyId>,
+    /// Holds the location of the connection on body A, in body space.
+    pub connection_point_a: Vec3,
+    /// Holds the location of the connection on body B, in body space.
+    pub connection_point_b: Vec3,
+    /// Holds the force above which the joint breaks. Unbreakable
+    /// joints have no break force.
+    pub break_force: Option<Real>,
+    /// Holds the torque above which the joint breaks, as when it is
+    /// twisted or bent. Joints with no break torque can't be broken
+    /// that way.
+    pub break_torque: Option<Real>,
+    rest_orientation: Quat,
+    load: JointLoad,
+}
+
+impl FixedJoint {
+    /// Welds the bodies together in their current configuration,
+    /// connecting them at the centre of body A.
+    pub fn new(bodies: &RigidBodySet, body_a: RigidBodyId, body_b: Option<RigidBodyId>) -> Self {
+        let anchor = bodies[body_a].position;
+        let transform_b = body_transform(body_b.map(|body_b| &bodies[body_b]));
+
+        Self {
+            body_a,
+            body_b,
+            connection_point_a: Vec3::ZERO,
+            connection_point_b: transform_b.transform_inverse(anchor),
+            break_force: None,
+            break_torque: None,
+            rest_orientation: relative_orientation(
+                &bodies[body_a],
+                body_b.map(|body_b| &bodies[body_b]),
+            ),
+            load: JointLoad::default(),
+        }
+    }
+
+    pub fn with_break_force(mut self, break_force: Real) -> Self {
+        self.break_force = Some(break_force);
+        self
+    }
+
+    pub fn with_break_torque(mut self, break_torque: Real) -> Self {
+        self.break_torque = Some(break_torque);
+        self
+    }
+}
+
+impl Joint for FixedJoint {
+    fn prepare(&mut self, _bodies: &RigidBodySet, _duration: Real) {
+        self.load.reset();
+    }
+
+    fn resolve_velocity(&mut self, bodies: &mut RigidBodySet, _duration: Real) {
+        let mut pair = BodyPair::new(bodies, self.body_a, self.body_b);
+        let angular_impulse = pair.resolve_rotation_lock_velocity();
+        self.load.add_angular_impulse(angular_impulse);
+
+        let point_a = pair.a.get_point_in_world_space(self.connection_point_a);
+        let point_b = pair.point_in_world_space_b(self.connection_point_b);
+        let impulse = pair.resolve_point_velocity(point_a, point_b, 0.0);
+        self.load.add_impulse(impulse);
+    }
+
+    fn resolve_position(&mut self, bodies: &mut RigidBodySet, _duration: Real) {
+        let mut pair = BodyPair::new(bodies, self.body_a, self.body_b);
+        pair.resolve_rotation_lock_position(self.rest_orientation);
+
+        let point_a = pair.a.get_point_in_world_space(self.connection_point_a);
+        let point_b = pair.point_in_world_space_b(self.connection_point_b);
+        pair.resolve_point_position(point_a, point_b, 0.0);
+    }
+
+    fn check_break(&mut self, duration: Real) -> bool {
+        self.load
+            .check_break(self.break_force, self.break_torque, duration)
+    }
+
+    fn is_broken(&self) -> bool {
+        self.load.is_broken()
+    }
+}
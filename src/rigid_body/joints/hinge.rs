@@ -0,0 +1,221 @@
+use crate::{
+    consts::PI,
+    precision::Real,
+    rigid_body::{RigidBodyId, RigidBodySet},
+    Mat4, Vec3,
+};
+
+use super::{
+    constraint::{body_transform, perpendicular_axes, BodyPair, JointLoad},
+    Joint, JointLimits, JointMotor,
+};
+
+/// Joins two rigid bodies together at a point and only lets them
+/// rotate relative to each other around a single axis, like a door
+/// on its hinges.
+///
+/// The angle of the hinge is how far body A has turned around the
+/// axis relative to body B, and is zero in the configuration the
+/// hinge was created in.
+#[derive(Debug, Clone)]
+pub struct HingeJoint {
+    pub body_a: RigidBodyId,
+    /// When there is no second body, the hinge is attached to the
+    /// world, and everything given for body B is in world space.
+    pub body_b: Option<RigidBodyId>,
+    /// Holds the location of the connection on body A, in body space.
+    pub connection_point_a: Vec3,
+    /// Holds the location of the connection on body B, in body space.
+    pub connection_point_b: Vec3,
+    /// Holds the axis of the hinge, in the space of body A.
+    pub axis_a: Vec3,
+    /// Holds the axis of the hinge, in the space of body B.
+    pub axis_b: Vec3,
+    /// Holds the range of angles, in radians, the hinge can turn
+    /// through.
+    pub limits: Option<JointLimits>,
+    /// Holds the motor driving the hinge. Its target velocity is
+    /// in radians per second, and its maximum force is a torque.
+    pub motor: Option<JointMotor>,
+    /// Holds the force above which the joint breaks. Unbreakable
+    /// joints have no break force.
+    pub break_force: Option<Real>,
+    /// Holds the torque above which the joint breaks, as when it is
+    /// twisted or bent. Joints with no break torque can't be broken
+    /// that way.
+    pub break_torque: Option<Real>,
+    reference_a: Vec3,
+    reference_b: Vec3,
+    motor_impulse: Real,
+    load: JointLoad,
+}
+
+impl HingeJoint {
+    /// Creates a hinge at the given anchor, turning around the given
+    /// axis. Both are given in world space.
+    pub fn new(
+        bodies: &RigidBodySet,
+        body_a: RigidBodyId,
+        body_b: Option<RigidBodyId>,
+        anchor: Vec3,
+        axis: Vec3,
+    ) -> Self {
+        let axis = axis.normalized();
+        let (reference, _) = perpendicular_axes(axis);
+        let transform_a = body_transform(Some(&bodies[body_a]));
+        let transform_b = body_transform(body_b.map(|body_b| &bodies[body_b]));
+
+        Self {
+            body_a,
+            body_b,
+            connection_point_a: transform_a.transform_inverse(anchor),
+            connection_point_b: transform_b.transform_inverse(anchor),
+            axis_a: transform_a.transform_inverse_direction(axis),
+            axis_b: transform_b.transform_inverse_direction(axis),
+            limits: None,
+            motor: None,
+            break_force: None,
+            break_torque: None,
+            reference_a: transform_a.transform_inverse_direction(reference),
+            reference_b: transform_b.transform_inverse_direction(reference),
+            motor_impulse: 0.0,
+            load: JointLoad::default(),
+        }
+    }
+
+    pub fn with_limits(mut self, limits: JointLimits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
+    pub fn with_motor(mut self, motor: JointMotor) -> Self {
+        self.motor = Some(motor);
+        self
+    }
+
+    pub fn with_break_force(mut self, break_force: Real) -> Self {
+        self.break_force = Some(break_force);
+        self
+    }
+
+    pub fn with_break_torque(mut self, break_torque: Real) -> Self {
+        self.break_torque = Some(break_torque);
+        self
+    }
+
+    /// Returns the current angle of the hinge, in radians.
+    pub fn angle(&self, bodies: &RigidBodySet) -> Real {
+        let transform_a = bodies[self.body_a].transform_matrix();
+        let transform_b = self
+            .body_b
+            .map_or(Mat4::IDENTITY, |body_b| bodies[body_b].transform_matrix());
+
+        hinge_angle(
+            transform_a.transform_direction(self.axis_a),
+            transform_b.transform_direction(self.reference_b),
+            transform_a.transform_direction(self.reference_a),
+        )
+    }
+
+    fn pair_angle(&self, pair: &BodyPair) -> Real {
+        let transform_a = pair.a.transform_matrix();
+
+        hinge_angle(
+            transform_a.transform_direction(self.axis_a),
+            pair.direction_in_world_space_b(self.reference_b),
+            transform_a.transform_direction(self.reference_a),
+        )
+    }
+}
+
+impl Joint for HingeJoint {
+    fn prepare(&mut self, _bodies: &RigidBodySet, _duration: Real) {
+        self.motor_impulse = 0.0;
+        self.load.reset();
+    }
+
+    fn resolve_velocity(&mut self, bodies: &mut RigidBodySet, duration: Real) {
+        let mut pair = BodyPair::new(bodies, self.body_a, self.body_b);
+        let point_a = pair.a.get_point_in_world_space(self.connection_point_a);
+        let point_b = pair.point_in_world_space_b(self.connection_point_b);
+        let impulse = pair.resolve_point_velocity(point_a, point_b, 0.0);
+        self.load.add_impulse(impulse);
+
+        let axis = pair.a.transform_matrix().transform_direction(self.axis_a);
+        let (perpendicular_a, perpendicular_b) = perpendicular_axes(axis);
+        let impulse_a = pair.resolve_angular_velocity_along(perpendicular_a, 0.0);
+        let impulse_b = pair.resolve_angular_velocity_along(perpendicular_b, 0.0);
+        self.load
+            .add_angular_impulse(perpendicular_a * impulse_a + perpendicular_b * impulse_b);
+
+        // The hinge turns body A relative to body B, so its free axis
+        // points the opposite way to the relative rotation of body B.
+        let axis = -axis;
+
+        if let Some(motor) = self.motor {
+            let inverse_mass = pair.angular_inverse_mass_along(axis);
+            if inverse_mass > 0.0 {
+                let speed = pair.relative_angular_velocity().dot(axis);
+                let max_impulse = motor.max_force * duration;
+                let previous_impulse = self.motor_impulse;
+                self.motor_impulse = (previous_impulse
+                    + (motor.target_velocity - speed) / inverse_mass)
+                    .clamp(-max_impulse, max_impulse);
+                pair.add_angular_impulse(axis * (self.motor_impulse - previous_impulse));
+            }
+        }
+
+        if let Some(limits) = self.limits {
+            let angle = self.pair_angle(&pair);
+            let speed = pair.relative_angular_velocity().dot(axis);
+            if angle <= limits.lower && speed < 0.0 || angle >= limits.upper && speed > 0.0 {
+                let impulse = pair.resolve_angular_velocity_along(axis, 0.0);
+                self.load.add_angular_impulse(axis * impulse);
+            }
+        }
+    }
+
+    fn resolve_position(&mut self, bodies: &mut RigidBodySet, _duration: Real) {
+        let mut pair = BodyPair::new(bodies, self.body_a, self.body_b);
+
+        let axis_a = pair.a.transform_matrix().transform_direction(self.axis_a);
+        let axis_b = pair.direction_in_world_space_b(self.axis_b);
+        let error = axis_b.cross(axis_a);
+        let (perpendicular_a, perpendicular_b) = perpendicular_axes(axis_a);
+        pair.rotate_along(perpendicular_a, perpendicular_a.dot(error));
+        pair.rotate_along(perpendicular_b, perpendicular_b.dot(error));
+
+        if let Some(limits) = self.limits {
+            let angle = self.pair_angle(&pair);
+            if angle < limits.lower {
+                pair.rotate_along(-axis_a, limits.lower - angle);
+            } else if angle > limits.upper {
+                pair.rotate_along(-axis_a, limits.upper - angle);
+            }
+        }
+
+        let point_a = pair.a.get_point_in_world_space(self.connection_point_a);
+        let point_b = pair.point_in_world_space_b(self.connection_point_b);
+        pair.resolve_point_position(point_a, point_b, 0.0);
+    }
+
+    fn check_break(&mut self, duration: Real) -> bool {
+        self.load
+            .check_break(self.break_force, self.break_torque, duration)
+    }
+
+    fn is_broken(&self) -> bool {
+        self.load.is_broken()
+    }
+}
+
+/// Returns the angle, in the range [-PI, PI], that the second
+/// reference vector is turned from the first around the axis.
+fn hinge_angle(axis: Vec3, reference_a: Vec3, reference_b: Vec3) -> Real {
+    let angle = reference_a
+        .cross(reference_b)
+        .dot(axis)
+        .atan2(reference_a.dot(reference_b));
+
+    angle.clamp(-PI, PI)
+}
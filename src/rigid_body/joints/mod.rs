@@ -0,0 +1,178 @@
+mod ball;
+mod constraint;
+mod fixed;
+mod hinge;
+mod slider;
+
+pub use ball::BallJoint;
+pub use fixed::FixedJoint;
+pub use hinge::HingeJoint;
+pub use slider::SliderJoint;
+
+use derive_more::{From, Index, IndexMut, IntoIterator};
+use downcast_rs::{impl_downcast, Downcast};
+use slotmap::{new_key_type, SlotMap};
+
+use crate::precision::Real;
+
+use super::RigidBodySet;
+
+/// Joints link together two rigid bodies (or a rigid body and a
+/// fixed point in the world) and remove some of their relative
+/// freedom of movement.
+///
+/// Joints are resolved after the bodies have been integrated: first
+/// the relative velocity the joint doesn't allow is removed with
+/// impulses, then the bodies are moved back into a configuration
+/// that satisfies the joint.
+pub trait Joint: Downcast {
+    /// Called once per step, before the joint is resolved.
+    fn prepare(&mut self, _bodies: &RigidBodySet, _duration: Real) {}
+    fn resolve_velocity(&mut self, bodies: &mut RigidBodySet, duration: Real);
+    fn resolve_position(&mut self, bodies: &mut RigidBodySet, duration: Real);
+
+    /// Checks the load the joint carried while its velocity was
+    /// resolved this step, and breaks the joint if it was more than
+    /// it can take. Returns whether the joint is broken.
+    fn check_break(&mut self, _duration: Real) -> bool {
+        false
+    }
+
+    /// Broken joints are no longer resolved.
+    fn is_broken(&self) -> bool {
+        false
+    }
+}
+
+/// Restricts the movement a joint allows along (or around) its free
+/// axis to a range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JointLimits {
+    pub lower: Real,
+    pub upper: Real,
+}
+
+impl JointLimits {
+    pub fn new(lower: Real, upper: Real) -> Self {
+        assert!(
+            lower <= upper,
+            "The lower limit can't be above the upper limit"
+        );
+        Self { lower, upper }
+    }
+}
+
+/// Drives a joint along (or around) its free axis at a target
+/// velocity, without applying more than a maximum force (or torque).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JointMotor {
+    pub target_velocity: Real,
+    pub max_force: Real,
+}
+
+new_key_type! {
+    pub struct JointId;
+}
+
+#[derive(Default, IntoIterator, Index, IndexMut, From)]
+pub struct JointSet {
+    inner: SlotMap<JointId, Box<dyn Joint>>,
+}
+
+impl_downcast!(Joint);
+
+impl JointSet {
+    pub fn new() -> Self {
+        Self {
+            inner: SlotMap::with_key(),
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            inner: SlotMap::with_capacity_and_key(capacity),
+        }
+    }
+
+    /// Resolves every joint that isn't broken against the bodies.
+    /// Returns the joints that broke during this step.
+    pub fn resolve(
+        &mut self,
+        bodies: &mut RigidBodySet,
+        iterations: u32,
+        duration: Real,
+    ) -> Vec<JointId> {
+        for joint in self.active_joints() {
+            joint.prepare(bodies, duration);
+        }
+
+        for _ in 0..iterations {
+            for joint in self.active_joints() {
+                joint.resolve_velocity(bodies, duration);
+            }
+        }
+
+        let broken = self
+            .inner
+            .iter_mut()
+            .filter(|(_, joint)| !joint.is_broken())
+            .filter_map(|(id, joint)| joint.check_break(duration).then_some(id))
+            .collect();
+
+        for _ in 0..iterations {
+            for joint in self.active_joints() {
+                joint.resolve_position(bodies, duration);
+            }
+        }
+
+        broken
+    }
+
+    fn active_joints(&mut self) -> impl Iterator<Item = &mut Box<dyn Joint>> {
+        self.inner.values_mut().filter(|joint| !joint.is_broken())
+    }
+
+    pub fn insert<J: Joint + 'static>(&mut self, value: J) -> JointId {
+        self.inner.insert(Box::new(value))
+    }
+
+    pub fn delete(&mut self, key: JointId) {
+        self.inner.remove(key);
+    }
+
+    pub fn remove<J: Joint>(&mut self, key: JointId) -> Option<Box<J>> {
+        self.inner.remove(key)?.downcast().ok()
+    }
+
+    pub fn clear(&mut self) {
+        self.inner.clear()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn get<J: Joint>(&self, key: JointId) -> Option<&J> {
+        self.inner.get(key)?.downcast_ref()
+    }
+
+    pub fn get_mut<J: Joint>(&mut self, key: JointId) -> Option<&mut J> {
+        self.inner.get_mut(key)?.downcast_mut()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    pub fn contains(&self, key: JointId) -> bool {
+        self.inner.contains_key(key)
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.inner.reserve(additional)
+    }
+}
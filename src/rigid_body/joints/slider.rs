@@ -0,0 +1,178 @@
+use crate::{
+    precision::Real,
+    rigid_body::{RigidBodyId, RigidBodySet},
+    Quat, Vec3,
+};
+
+use super::{
+    constraint::{body_transform, perpendicular_axes, relative_orientation, BodyPair, JointLoad},
+    Joint, JointLimits,
+};
+
+/// Joins two rigid bodies so that they can only slide relative to
+/// each other along a single axis, like a piston in its cylinder.
+/// The bodies can't rotate relative to each other.
+///
+/// The translation of the slider is how far the connection point on
+/// body A has moved along the axis from the connection point on body
+/// B, and is zero in the configuration the slider was created in.
+#[derive(Debug, Clone)]
+pub struct SliderJoint {
+    pub body_a: RigidBodyId,
+    /// When there is no second body, the slider is attached to the
+    /// world, and everything given for body B is in world space.
+    pub body_b: Option<RigidBodyId>,
+    /// Holds the location of the connection on body A, in body space.
+    pub connection_point_a: Vec3,
+    /// Holds the location of the connection on body B, in body space.
+    pub connection_point_b: Vec3,
+    /// Holds the direction the bodies slide along, in the space of
+    /// body A.
+    pub axis: Vec3,
+    /// Holds the range of translations the slider can move through.
+    pub limits: Option<JointLimits>,
+    /// Holds the force above which the joint breaks. Unbreakable
+    /// joints have no break force.
+    pub break_force: Option<Real>,
+    /// Holds the torque above which the joint breaks, as when it is
+    /// twisted or bent. Joints with no break torque can't be broken
+    /// that way.
+    pub break_torque: Option<Real>,
+    rest_orientation: Quat,
+    load: JointLoad,
+}
+
+impl SliderJoint {
+    /// Creates a slider at the given anchor, moving along the given
+    /// axis. Both are given in world space.
+    pub fn new(
+        bodies: &RigidBodySet,
+        body_a: RigidBodyId,
+        body_b: Option<RigidBodyId>,
+        anchor: Vec3,
+        axis: Vec3,
+    ) -> Self {
+        let transform_a = body_transform(Some(&bodies[body_a]));
+        let transform_b = body_transform(body_b.map(|body_b| &bodies[body_b]));
+
+        Self {
+            body_a,
+            body_b,
+            connection_point_a: transform_a.transform_inverse(anchor),
+            connection_point_b: transform_b.transform_inverse(anchor),
+            axis: transform_a.transform_inverse_direction(axis.normalized()),
+            limits: None,
+            break_force: None,
+            break_torque: None,
+            rest_orientation: relative_orientation(
+                &bodies[body_a],
+                body_b.map(|body_b| &bodies[body_b]),
+            ),
+            load: JointLoad::default(),
+        }
+    }
+
+    pub fn with_limits(mut self, limits: JointLimits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
+    pub fn with_break_force(mut self, break_force: Real) -> Self {
+        self.break_force = Some(break_force);
+        self
+    }
+
+    pub fn with_break_torque(mut self, break_torque: Real) -> Self {
+        self.break_torque = Some(break_torque);
+        self
+    }
+
+    /// Returns the current translation of the slider.
+    pub fn translation(&self, bodies: &RigidBodySet) -> Real {
+        let body_a = &bodies[self.body_a];
+        let point_a = body_a.get_point_in_world_space(self.connection_point_a);
+        let point_b = self.body_b.map_or(self.connection_point_b, |body_b| {
+            bodies[body_b].get_point_in_world_space(self.connection_point_b)
+        });
+        let axis = body_a.transform_matrix().transform_direction(self.axis);
+
+        (point_a - point_b).dot(axis)
+    }
+}
+
+impl Joint for SliderJoint {
+    fn prepare(&mut self, _bodies: &RigidBodySet, _duration: Real) {
+        self.load.reset();
+    }
+
+    fn resolve_velocity(&mut self, bodies: &mut RigidBodySet, _duration: Real) {
+        let mut pair = BodyPair::new(bodies, self.body_a, self.body_b);
+        let angular_impulse = pair.resolve_rotation_lock_velocity();
+        self.load.add_angular_impulse(angular_impulse);
+
+        let point_a = pair.a.get_point_in_world_space(self.connection_point_a);
+        let point_b = pair.point_in_world_space_b(self.connection_point_b);
+        let axis = pair.a.transform_matrix().transform_direction(self.axis);
+        let (perpendicular_a, perpendicular_b) = perpendicular_axes(axis);
+        let impulse_a = pair.resolve_velocity_along(point_a, point_b, perpendicular_a, 0.0);
+        let impulse_b = pair.resolve_velocity_along(point_a, point_b, perpendicular_b, 0.0);
+        self.load
+            .add_impulse(perpendicular_a * impulse_a + perpendicular_b * impulse_b);
+
+        if let Some(limits) = self.limits {
+            // The slider moves body A relative to body B, so its free axis
+            // points the opposite way to the relative movement of body B.
+            let axis = -axis;
+            let translation = (point_b - point_a).dot(axis);
+            let speed = pair.relative_point_velocity(point_a, point_b).dot(axis);
+            if translation <= limits.lower && speed < 0.0
+                || translation >= limits.upper && speed > 0.0
+            {
+                let impulse = pair.resolve_velocity_along(point_a, point_b, axis, 0.0);
+                self.load.add_impulse(axis * impulse);
+            }
+        }
+    }
+
+    fn resolve_position(&mut self, bodies: &mut RigidBodySet, _duration: Real) {
+        let mut pair = BodyPair::new(bodies, self.body_a, self.body_b);
+        pair.resolve_rotation_lock_position(self.rest_orientation);
+
+        let point_a = pair.a.get_point_in_world_space(self.connection_point_a);
+        let point_b = pair.point_in_world_space_b(self.connection_point_b);
+        let axis = pair.a.transform_matrix().transform_direction(self.axis);
+        let delta = point_b - point_a;
+        let (perpendicular_a, perpendicular_b) = perpendicular_axes(axis);
+        pair.translate_along(
+            point_a,
+            point_b,
+            perpendicular_a,
+            -delta.dot(perpendicular_a),
+        );
+        pair.translate_along(
+            point_a,
+            point_b,
+            perpendicular_b,
+            -delta.dot(perpendicular_b),
+        );
+
+        if let Some(limits) = self.limits {
+            let axis = -axis;
+            let translation = delta.dot(axis);
+            if translation < limits.lower {
+                pair.translate_along(point_a, point_b, axis, limits.lower - translation);
+            } else if translation > limits.upper {
+                pair.translate_along(point_a, point_b, axis, limits.upper - translation);
+            }
+        }
+    }
+
+    fn check_break(&mut self, duration: Real) -> bool {
+        self.load
+            .check_break(self.break_force, self.break_torque, duration)
+    }
+
+    fn is_broken(&self) -> bool {
+        self.load.is_broken()
+    }
+}
@@ -0,0 +1,254 @@
+use crate::{consts::PI, precision::Real, Mat3, Mat4, Vec3};
+
+use super::{
+    collide_narrow::{Capsule, Collider, Cuboid, Cylinder, Primitive, PrimitiveShape, Sphere},
+    BodyType, RigidBody,
+};
+
+/// Holds the mass of a shape, where its centre of mass is, and its
+/// inertia tensor about the centre of mass.
+///
+/// The centre of mass and the axes of the tensor are given in the
+/// space the shape was built in. For a single primitive that is its
+/// own space, and for a collider it is body space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MassProperties {
+    pub mass: Real,
+    pub centre_of_mass: Vec3,
+    pub inertia_tensor: Mat3,
+}
+
+impl MassProperties {
+    /// The mass properties of nothing at all, which adds nothing
+    /// when combined with others.
+    pub const ZERO: Self = Self {
+        mass: 0.0,
+        centre_of_mass: Vec3::ZERO,
+        inertia_tensor: Mat3::new([0.0; 9]),
+    };
+
+    pub fn sphere(sphere: Sphere, density: Real) -> Self {
+        let r = sphere.radius;
+        let mass = density * 4.0 / 3.0 * PI * r * r * r;
+
+        Self {
+            mass,
+            centre_of_mass: Vec3::ZERO,
+            inertia_tensor: diagonal(Vec3::splat(0.4 * mass * r * r)),
+        }
+    }
+
+    pub fn cuboid(cuboid: Cuboid, density: Real) -> Self {
+        let Vec3 { x, y, z } = cuboid.half_size;
+        let mass = density * 8.0 * x * y * z;
+
+        Self {
+            mass,
+            centre_of_mass: Vec3::ZERO,
+            inertia_tensor: diagonal(
+                Vec3::new(y * y + z * z, x * x + z * z, x * x + y * y) * (mass / 3.0),
+            ),
+        }
+    }
+
+    pub fn cylinder(cylinder: Cylinder, density: Real) -> Self {
+        let r = cylinder.radius;
+        let h = cylinder.half_height;
+        let mass = density * PI * r * r * 2.0 * h;
+
+        let across = mass * (3.0 * r * r + 4.0 * h * h) / 12.0;
+        let along = 0.5 * mass * r * r;
+
+        Self {
+            mass,
+            centre_of_mass: Vec3::ZERO,
+            inertia_tensor: diagonal(Vec3::new(across, along, across)),
+        }
+    }
+
+    pub fn capsule(capsule: Capsule, density: Real) -> Self {
+        let r = capsule.radius;
+        let h = capsule.half_height;
+
+        // The capsule is a cylinder, plus a sphere split between the
+        // two ends. Each half of the sphere is moved away from the
+        // centre, by the parallel axis theorem.
+        let cylinder = Self::cylinder(
+            Cylinder {
+                radius: r,
+                half_height: h,
+            },
+            density,
+        );
+        let caps = density * 4.0 / 3.0 * PI * r * r * r;
+
+        let across = caps * (0.4 * r * r + h * h + 0.75 * h * r);
+        let along = caps * 0.4 * r * r;
+
+        Self {
+            mass: cylinder.mass + caps,
+            centre_of_mass: Vec3::ZERO,
+            inertia_tensor: cylinder.inertia_tensor + diagonal(Vec3::new(across, along, across)),
+        }
+    }
+
+    /// Returns the mass properties of a primitive, in the space its
+    /// offset moves it into. Planes are infinite half-spaces, so they
+    /// have none.
+    pub fn primitive(primitive: &Primitive, density: Real) -> Self {
+        let properties = match primitive.shape {
+            PrimitiveShape::Sphere(sphere) => Self::sphere(sphere, density),
+            PrimitiveShape::Plane(_) => return Self::ZERO,
+            PrimitiveShape::Cuboid(cuboid) => Self::cuboid(cuboid, density),
+            PrimitiveShape::Capsule(capsule) => Self::capsule(capsule, density),
+            PrimitiveShape::Cylinder(cylinder) => Self::cylinder(cylinder, density),
+        };
+
+        properties.transformed(primitive.offset)
+    }
+
+    /// Returns the mass properties of every primitive of the collider
+    /// combined, in body space.
+    pub fn collider(collider: &Collider, density: Real) -> Self {
+        match collider {
+            Collider::Single(primitive) => Self::primitive(primitive, density),
+            Collider::Set(primitives) => primitives
+                .iter()
+                .map(|primitive| Self::primitive(primitive, density))
+                .fold(Self::ZERO, Self::combine),
+        }
+    }
+
+    /// Returns the mass properties moved and rotated by the given
+    /// transform.
+    pub fn transformed(self, transform: Mat4) -> Self {
+        let rotation = Mat3::new([
+            transform.data[0],
+            transform.data[1],
+            transform.data[2],
+            transform.data[4],
+            transform.data[5],
+            transform.data[6],
+            transform.data[8],
+            transform.data[9],
+            transform.data[10],
+        ]);
+
+        Self {
+            mass: self.mass,
+            centre_of_mass: transform.transform(self.centre_of_mass),
+            inertia_tensor: rotation
+                .mul_mat3(self.inertia_tensor)
+                .mul_mat3(rotation.transpose()),
+        }
+    }
+
+    /// Returns the mass properties of both shapes together. The
+    /// tensors are moved to the new centre of mass by the parallel
+    /// axis theorem.
+    pub fn combine(self, other: Self) -> Self {
+        let mass = self.mass + other.mass;
+        if mass <= 0.0 {
+            return Self::ZERO;
+        }
+
+        let centre_of_mass =
+            (self.centre_of_mass * self.mass + other.centre_of_mass * other.mass) / mass;
+
+        Self {
+            mass,
+            centre_of_mass,
+            inertia_tensor: self.inertia_tensor
+                + parallel_axis(self.mass, self.centre_of_mass - centre_of_mass)
+                + other.inertia_tensor
+                + parallel_axis(other.mass, other.centre_of_mass - centre_of_mass),
+        }
+    }
+}
+
+impl RigidBody {
+    /// Creates a body with the mass and inertia tensor of the given
+    /// collider, made of a material of the given density. Returns the
+    /// centre of mass of the collider alongside it.
+    ///
+    /// The position of a body is its centre of mass, so a collider
+    /// whose centre of mass isn't at its origin should be moved to
+    /// match, with [`Collider::translate`] and the negated centre of
+    /// mass. To keep the collider where it was, place the body that
+    /// much further along.
+    ///
+    /// Colliders made only of planes have no mass, and give a static
+    /// body instead.
+    pub fn from_collider(collider: &Collider, density: Real) -> (Self, Vec3) {
+        let properties = MassProperties::collider(collider, density);
+        if properties.mass <= 0.0 {
+            let body = RigidBody::new(Real::INFINITY)
+                .with_body_type(BodyType::Static)
+                .with_inverse_inertia_tensor(Mat3::new([0.0; 9]));
+            return (body, Vec3::ZERO);
+        }
+
+        let body = RigidBody::new(properties.mass).with_inertia_tensor(properties.inertia_tensor);
+        (body, properties.centre_of_mass)
+    }
+}
+
+fn diagonal(values: Vec3) -> Mat3 {
+    Mat3::new([values.x, 0.0, 0.0, 0.0, values.y, 0.0, 0.0, 0.0, values.z])
+}
+
+/// Returns what the inertia tensor of a mass grows by when its axes
+/// are moved by the given offset.
+fn parallel_axis(mass: Real, offset: Vec3) -> Mat3 {
+    let Vec3 { x, y, z } = offset;
+
+    Mat3::new([
+        y * y + z * z,
+        -x * y,
+        -x * z,
+        -x * y,
+        x * x + z * z,
+        -y * z,
+        -x * z,
+        -y * z,
+        x * x + y * y,
+    ]) * mass
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{rigid_body::collide_narrow::Plane, Quat};
+
+    use super::*;
+
+    #[test]
+    fn off_centre_collider_gives_its_centre_of_mass() {
+        let mut collider = Collider::Single(Primitive {
+            offset: Mat4::from_orientation_and_position(Quat::IDENTITY, Vec3::new(1.0, 2.0, 3.0)),
+            shape: PrimitiveShape::Sphere(Sphere { radius: 0.5 }),
+        });
+
+        let (body, centre_of_mass) = RigidBody::from_collider(&collider, 1.0);
+        assert!(body.is_dynamic());
+        assert!((centre_of_mass - Vec3::new(1.0, 2.0, 3.0)).magnitude() < 1e-5);
+
+        collider.translate(-centre_of_mass);
+        let recentred = MassProperties::collider(&collider, 1.0);
+        assert!(recentred.centre_of_mass.magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn planes_give_a_static_body() {
+        let collider = Collider::Single(Primitive {
+            offset: Mat4::IDENTITY,
+            shape: PrimitiveShape::Plane(Plane {
+                normal: Vec3::Y,
+                offset: 0.0,
+            }),
+        });
+
+        let (body, centre_of_mass) = RigidBody::from_collider(&collider, 1.0);
+        assert_eq!(body.body_type, BodyType::Static);
+        assert_eq!(centre_of_mass, Vec3::ZERO);
+    }
+}
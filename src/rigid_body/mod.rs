@@ -1,8 +1,15 @@
 pub mod collide_broad;
 pub mod collide_narrow;
 pub mod fgen;
+mod integrator;
+pub mod joints;
+mod mass;
+mod stepper;
 mod system;
 
+pub use integrator::Integrator;
+pub use mass::MassProperties;
+pub use stepper::FixedStepper;
 pub use system::PhysicsSystem;
 
 use slotmap::{new_key_type, SlotMap};
@@ -11,6 +18,23 @@ use derive_more::{From, Index, IndexMut, IntoIterator};
 
 use crate::{precision::Real, Mat3, Mat4, Quat, Vec3};
 
+/// How a rigid body takes part in the simulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BodyType {
+    /// The body is moved by the forces, impulses and joints acting
+    /// on it.
+    #[default]
+    Dynamic,
+    /// The body never moves. It is never integrated, and its
+    /// velocity is kept at zero.
+    Static,
+    /// The body is moved by its velocity, or towards a target
+    /// transform, but nothing else. Joints treat it as though it had
+    /// infinite mass, so it pushes dynamic bodies without being
+    /// pushed back, as for moving platforms and doors.
+    Kinematic,
+}
+
 /// A rigid body is the basic simulation object in the physics
 /// core.
 ///
@@ -21,6 +45,7 @@ use crate::{precision::Real, Mat3, Mat4, Quat, Vec3};
 /// through a set of methods.
 #[derive(Debug, Clone)]
 pub struct RigidBody {
+    pub body_type: BodyType,
     pub inverse_mass: Real,
     /// Holds the amount of damping applied to linear
     /// motion. Damping is required to remove energy added
@@ -57,6 +82,7 @@ pub struct RigidBody {
     torque_accum: Vec3,
     is_awake: bool,
     last_frame_acceleration: Vec3,
+    kinematic_target: Option<(Vec3, Quat)>,
 }
 
 impl RigidBody {
@@ -69,6 +95,7 @@ impl RigidBody {
         };
 
         Self {
+            body_type: BodyType::Dynamic,
             inverse_mass,
             damping: 0.99,
             angular_damping: 0.99,
@@ -84,6 +111,7 @@ impl RigidBody {
             torque_accum: Vec3::ZERO,
             is_awake: true,
             last_frame_acceleration: Vec3::ZERO,
+            kinematic_target: None,
         }
     }
 
@@ -92,6 +120,11 @@ impl RigidBody {
         self
     }
 
+    pub fn with_body_type(mut self, body_type: BodyType) -> Self {
+        self.body_type = body_type;
+        self
+    }
+
     pub fn with_inverse_mass(mut self, inverse_mass: Real) -> Self {
         self.inverse_mass = inverse_mass;
         self
@@ -168,11 +201,60 @@ impl RigidBody {
         self.inverse_inertia_tensor = inertia_tensor.inverse();
     }
 
+    pub fn is_dynamic(&self) -> bool {
+        self.body_type == BodyType::Dynamic
+    }
+
+    /// Returns the inverse mass the body has when it is pushed. Only
+    /// dynamic bodies can be pushed, so the others act as though
+    /// their mass was infinite.
+    pub fn effective_inverse_mass(&self) -> Real {
+        if self.is_dynamic() {
+            self.inverse_mass
+        } else {
+            0.0
+        }
+    }
+
+    /// Sets the transform a kinematic body moves to in the next step.
+    /// The body gets the velocity that takes it there, so it pushes
+    /// what it meets along the way. Once there, it stays until it is
+    /// given another target, or the target is cleared.
+    pub fn set_kinematic_target(&mut self, position: Vec3, orientation: Quat) {
+        debug_assert_eq!(
+            self.body_type,
+            BodyType::Kinematic,
+            "Only kinematic bodies move to a target"
+        );
+        self.kinematic_target = Some((position, orientation.normalized()));
+        self.is_awake = true;
+    }
+
+    /// Clears the target of a kinematic body, so it moves with its
+    /// velocity again.
+    pub fn clear_kinematic_target(&mut self) {
+        self.kinematic_target = None;
+    }
+
     /// Integrates the rigid body forward in time by the given amount.
     /// This function uses a Newton-Euler integration method, which is a
     /// linear approximation to the correct integral. For this reason it
     /// may be inaccurate in some cases.
     pub fn integrate(&mut self, duration: Real) {
+        match self.body_type {
+            BodyType::Dynamic => {}
+            BodyType::Static => {
+                self.velocity = Vec3::ZERO;
+                self.angular_velocity = Vec3::ZERO;
+                self.clear_accumelators();
+                return;
+            }
+            BodyType::Kinematic => {
+                self.integrate_kinematic(duration);
+                return;
+            }
+        }
+
         self.last_frame_acceleration = self.acceleration + self.force_accum * self.inverse_mass;
         let angular_acceleration = self
             .inverse_inertia_tensor_world
@@ -193,6 +275,46 @@ impl RigidBody {
         self.clear_accumelators();
     }
 
+    /// Moves a kinematic body with its velocity, or to its target if
+    /// it has one. Forces and damping don't affect it.
+    fn integrate_kinematic(&mut self, duration: Real) {
+        if let Some((position, orientation)) = self.kinematic_target {
+            // A step that takes no time can't give the body the
+            // velocity to get there, so it is just put in place.
+            if duration <= 0.0 {
+                self.position = position;
+                self.orientation = orientation;
+                self.update_derived_data();
+                self.clear_accumelators();
+                return;
+            }
+
+            self.velocity = (position - self.position) / duration;
+
+            // The rotation that takes the body to its target, on the
+            // shorter way around.
+            let mut rotation = orientation * self.orientation.conjugate();
+            if rotation.r < 0.0 {
+                rotation = Quat::from_rijk(-rotation.r, -rotation.i, -rotation.j, -rotation.k);
+            }
+            let axis = Vec3::new(rotation.i, rotation.j, rotation.k);
+            let angle = 2.0 * axis.magnitude().atan2(rotation.r);
+            self.angular_velocity = axis.normalized() * (angle / duration);
+
+            self.position = position;
+            self.orientation = orientation;
+        } else {
+            self.position += self.velocity * duration;
+            self.orientation = self
+                .orientation
+                .add_scaled_vector(self.angular_velocity, duration);
+        }
+
+        self.last_frame_acceleration = Vec3::ZERO;
+        self.update_derived_data();
+        self.clear_accumelators();
+    }
+
     pub fn transform_matrix(&self) -> Mat4 {
         self.transform_matrix
     }
@@ -231,6 +353,43 @@ impl RigidBody {
         self.is_awake = true;
     }
 
+    /// Applies the given impulse to the given point on the rigid body,
+    /// changing its linear and angular velocity immediately. Both the
+    /// impulse and the application point are given in world space.
+    pub fn add_impulse_at_point(&mut self, impulse: Vec3, point: Vec3) {
+        let arm = point - self.position;
+        self.velocity += impulse * self.effective_inverse_mass();
+        self.angular_velocity += self
+            .inverse_inertia_tensor_world()
+            .transform(arm.cross(impulse));
+        self.is_awake = true;
+    }
+
+    /// Applies the given angular impulse to the rigid body. The impulse
+    /// is expressed in world-coordinates.
+    pub fn add_angular_impulse(&mut self, impulse: Vec3) {
+        self.angular_velocity += self.inverse_inertia_tensor_world().transform(impulse);
+        self.is_awake = true;
+    }
+
+    /// Returns the velocity of the given point on the rigid body,
+    /// taking its rotation into account. The point is given in
+    /// world space.
+    pub fn velocity_at_point(&self, point: Vec3) -> Vec3 {
+        self.velocity + self.angular_velocity.cross(point - self.position)
+    }
+
+    /// Returns the inverse inertia tensor of the body in world space.
+    /// Like [`RigidBody::effective_inverse_mass`], it is zero for
+    /// bodies that aren't dynamic.
+    pub fn inverse_inertia_tensor_world(&self) -> Mat3 {
+        if self.is_dynamic() {
+            self.inverse_inertia_tensor_world
+        } else {
+            Mat3::new([0.0; 9])
+        }
+    }
+
     /// Calculates internal data from state data. This should be called
     /// after the body's state is altered directly (it is called
     /// automatically during integration). If you change the body's state
@@ -241,7 +400,7 @@ impl RigidBody {
 
         self.transform_matrix =
             Mat4::from_orientation_and_position(self.orientation, self.position);
-        self.inverse_inertia_tensor = inverse_inertia_tensor_to_world_coords(
+        self.inverse_inertia_tensor_world = inverse_inertia_tensor_to_world_coords(
             self.inverse_inertia_tensor,
             self.transform_matrix,
         );
@@ -387,3 +546,66 @@ impl RigidBodySet {
         self.inner.drain()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inertia_tensor() -> Mat3 {
+        Mat3::new([1.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 0.0, 3.0])
+    }
+
+    fn orientation() -> Quat {
+        Quat::from_rijk(0.8, 0.3, -0.4, 0.2).normalized()
+    }
+
+    #[test]
+    fn world_inverse_inertia_tensor_is_rotated_into_world_space() {
+        let mut body = RigidBody::new(1.0)
+            .with_inertia_tensor(inertia_tensor())
+            .with_orientation(orientation());
+        body.update_derived_data();
+
+        // The columns of the rotation are where it takes the axes.
+        let [x, y, z] = [Vec3::X, Vec3::Y, Vec3::Z]
+            .map(|axis| body.transform_matrix().transform_direction(axis));
+        let rotation = Mat3::new([x.x, y.x, z.x, x.y, y.y, z.y, x.z, y.z, z.z]);
+        let expected = rotation
+            .mul_mat3(inertia_tensor().inverse())
+            .mul_mat3(rotation.transpose());
+
+        let world = body.inverse_inertia_tensor_world();
+        for (actual, expected) in world.data.iter().zip(expected.data) {
+            assert!((actual - expected).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn update_derived_data_leaves_body_space_tensor_alone() {
+        let mut body = RigidBody::new(1.0)
+            .with_inertia_tensor(inertia_tensor())
+            .with_orientation(orientation());
+        let inverse_inertia_tensor = body.inverse_inertia_tensor;
+
+        for _ in 0..1000 {
+            body.orientation = body
+                .orientation
+                .add_scaled_vector(Vec3::new(0.3, 1.0, -0.2), 0.01);
+            body.update_derived_data();
+        }
+
+        assert_eq!(body.inverse_inertia_tensor, inverse_inertia_tensor);
+    }
+
+    #[test]
+    fn kinematic_target_in_a_zero_length_step_keeps_velocity_finite() {
+        let position = Vec3::new(1.0, 0.0, 0.0);
+        let mut body = RigidBody::new(1.0).with_body_type(BodyType::Kinematic);
+        body.set_kinematic_target(position, orientation());
+        body.integrate(0.0);
+
+        assert_eq!(body.position, position);
+        assert_eq!(body.velocity, Vec3::ZERO);
+        assert_eq!(body.angular_velocity, Vec3::ZERO);
+    }
+}
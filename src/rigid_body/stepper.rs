@@ -0,0 +1,90 @@
+use slotmap::SecondaryMap;
+
+use crate::{precision::Real, timestep::FixedTimestep, Mat4, Quat, Vec3};
+
+use super::{RigidBodyId, RigidBodySet};
+
+/// Steps rigid bodies at a fixed rate from a render loop that runs at
+/// any rate, and interpolates their transforms for rendering.
+///
+/// Each call to [`FixedStepper::update`] runs as many fixed steps as
+/// the frame time allows, recording where every body was before each
+/// one. Between steps, the bodies are drawn partway from where they
+/// were to where they are.
+#[derive(Debug, Clone)]
+pub struct FixedStepper {
+    timestep: FixedTimestep,
+    previous: SecondaryMap<RigidBodyId, (Vec3, Quat)>,
+}
+
+impl FixedStepper {
+    pub fn new(step: Real) -> Self {
+        Self {
+            timestep: FixedTimestep::new(step),
+            previous: SecondaryMap::new(),
+        }
+    }
+
+    pub fn with_max_steps(mut self, max_steps: u32) -> Self {
+        self.timestep = self.timestep.with_max_steps(max_steps);
+        self
+    }
+
+    pub fn timestep(&self) -> &FixedTimestep {
+        &self.timestep
+    }
+
+    /// Adds the time of a frame, and calls `step` with the fixed
+    /// duration for every step it allows. The closure should run a
+    /// whole step of the simulation: starting the frame, applying the
+    /// forces, integrating and resolving contacts and joints. Returns
+    /// the number of steps taken.
+    pub fn update(
+        &mut self,
+        bodies: &mut RigidBodySet,
+        frame_time: Real,
+        mut step: impl FnMut(&mut RigidBodySet, Real),
+    ) -> u32 {
+        let steps = self.timestep.advance(frame_time);
+
+        for _ in 0..steps {
+            self.previous.clear();
+            for (id, body) in bodies.iter() {
+                self.previous.insert(id, (body.position, body.orientation));
+            }
+
+            step(bodies, self.timestep.step());
+        }
+
+        steps
+    }
+
+    /// Returns the position of the body to render, between its last
+    /// two steps.
+    pub fn interpolated_position(&self, bodies: &RigidBodySet, body: RigidBodyId) -> Vec3 {
+        let current = bodies[body].position;
+        match self.previous.get(body) {
+            Some(&(previous, _)) => previous.lerp(current, self.timestep.alpha()),
+            None => current,
+        }
+    }
+
+    /// Returns the orientation of the body to render, between its
+    /// last two steps.
+    pub fn interpolated_orientation(&self, bodies: &RigidBodySet, body: RigidBodyId) -> Quat {
+        let current = bodies[body].orientation;
+        match self.previous.get(body) {
+            Some(&(_, previous)) => previous.slerp(current, self.timestep.alpha()),
+            None => current,
+        }
+    }
+
+    /// Returns the transform of the body to render, between its last
+    /// two steps.
+    pub fn interpolated_transform(&self, bodies: &RigidBodySet, body: RigidBodyId) -> Mat4 {
+        Mat4::from_orientation_and_position(
+            self.interpolated_orientation(bodies, body),
+            self.interpolated_position(bodies, body),
+        )
+    }
+}
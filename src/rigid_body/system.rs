@@ -1,12 +1,38 @@
-use crate::precision::Real;
+use crate::{precision::Real, Quat, Vec3};
 
-use super::RigidBodySet;
+use super::{
+    fgen::{ForceGeneratorId, ForceGeneratorSet},
+    joints::{JointId, JointSet},
+    Integrator, RigidBodyId, RigidBodySet,
+};
 
-pub struct PhysicsSystem;
+pub struct PhysicsSystem {
+    joint_iterations: u32,
+    integrator: Integrator,
+    substeps: u32,
+    broken_generators: Vec<ForceGeneratorId>,
+    broken_joints: Vec<JointId>,
+}
 
 impl PhysicsSystem {
     pub fn new() -> Self {
-        Self
+        Self {
+            joint_iterations: 8,
+            integrator: Integrator::default(),
+            substeps: 1,
+            broken_generators: Vec::new(),
+            broken_joints: Vec::new(),
+        }
+    }
+
+    pub fn with_joint_iterations(mut self, joint_iterations: u32) -> Self {
+        self.joint_iterations = joint_iterations;
+        self
+    }
+
+    pub fn with_integrator(mut self, integrator: Integrator) -> Self {
+        self.integrator = integrator;
+        self
     }
 
     pub fn start_frame(&mut self, bodies: &mut RigidBodySet) {
@@ -16,15 +42,101 @@ impl PhysicsSystem {
         }
     }
 
-    pub fn step(&mut self, bodies: &mut RigidBodySet, duration: Real) {
-        self.integrate(bodies, duration);
+    /// Sets the number of passes each step is split into. The force
+    /// generators are run again for every pass, which keeps stiff
+    /// springs stable without shortening the steps themselves.
+    pub fn with_substeps(mut self, substeps: u32) -> Self {
+        assert!(substeps > 0, "A step needs at least one substep");
+        self.substeps = substeps;
+        self
+    }
+
+    /// Applies the force generators and integrates the bodies, in as
+    /// many substeps as the system has.
+    pub fn step(
+        &mut self,
+        bodies: &mut RigidBodySet,
+        generators: &mut ForceGeneratorSet,
+        duration: Real,
+    ) {
+        let substep = duration / self.substeps as Real;
+        let forces = accumulated_forces(bodies);
+        let targets = kinematic_targets(bodies);
+
+        self.broken_generators.clear();
+        for index in 0..self.substeps {
+            if index > 0 {
+                restore_forces(bodies, &forces);
+            }
+
+            let broken = generators.update_forces(bodies, substep);
+            self.broken_generators.extend(broken);
+            aim_kinematic_targets(bodies, &targets, self.substeps - index);
+            self.integrate(bodies, substep);
+        }
+
+        aim_kinematic_targets(bodies, &targets, 1);
+    }
+
+    /// Steps the bodies like [`PhysicsSystem::step`], but resolves the
+    /// given joints after every substep, rather than once after the
+    /// whole step.
+    pub fn step_with_joints(
+        &mut self,
+        bodies: &mut RigidBodySet,
+        generators: &mut ForceGeneratorSet,
+        joints: &mut JointSet,
+        duration: Real,
+    ) {
+        let substep = duration / self.substeps as Real;
+        let forces = accumulated_forces(bodies);
+        let targets = kinematic_targets(bodies);
+
+        self.broken_generators.clear();
+        self.broken_joints.clear();
+        for index in 0..self.substeps {
+            if index > 0 {
+                restore_forces(bodies, &forces);
+            }
+
+            let broken = generators.update_forces(bodies, substep);
+            self.broken_generators.extend(broken);
+            aim_kinematic_targets(bodies, &targets, self.substeps - index);
+            self.integrate(bodies, substep);
+
+            let broken = joints.resolve(bodies, self.joint_iterations, substep);
+            self.broken_joints.extend(broken);
+        }
+
+        aim_kinematic_targets(bodies, &targets, 1);
     }
 
     pub fn integrate(&mut self, bodies: &mut RigidBodySet, duration: Real) {
         for body in bodies.bodies_mut() {
-            body.integrate(duration);
+            self.integrator.integrate(body, duration);
         }
     }
+
+    /// Resolves the given joints against the bodies. This should be
+    /// called after the bodies have been stepped.
+    pub fn resolve_joints(
+        &mut self,
+        bodies: &mut RigidBodySet,
+        joints: &mut JointSet,
+        duration: Real,
+    ) {
+        self.broken_joints = joints.resolve(bodies, self.joint_iterations, duration);
+    }
+
+    /// Returns the force generators that broke during the last step.
+    pub fn broken_generators(&self) -> &[ForceGeneratorId] {
+        &self.broken_generators
+    }
+
+    /// Returns the joints that broke the last time joints were resolved.
+    pub fn broken_joints(&self) -> &[JointId] {
+        &self.broken_joints
+    }
 }
 
 impl Default for PhysicsSystem {
@@ -32,3 +144,98 @@ impl Default for PhysicsSystem {
         Self::new()
     }
 }
+
+/// Returns the forces and torques added to the bodies before a step,
+/// so they can be applied again in every substep.
+fn accumulated_forces(bodies: &RigidBodySet) -> Vec<(RigidBodyId, Vec3, Vec3)> {
+    bodies
+        .iter()
+        .filter(|(_, body)| body.force_accum != Vec3::ZERO || body.torque_accum != Vec3::ZERO)
+        .map(|(id, body)| (id, body.force_accum, body.torque_accum))
+        .collect()
+}
+
+fn restore_forces(bodies: &mut RigidBodySet, forces: &[(RigidBodyId, Vec3, Vec3)]) {
+    for &(id, force, torque) in forces {
+        if let Some(body) = bodies.get_mut(id) {
+            body.force_accum = force;
+            body.torque_accum = torque;
+        }
+    }
+}
+
+/// Returns the targets of the kinematic bodies, which they should
+/// reach at the end of a step rather than at the end of its first
+/// substep.
+fn kinematic_targets(bodies: &RigidBodySet) -> Vec<(RigidBodyId, Vec3, Quat)> {
+    bodies
+        .iter()
+        .filter_map(|(id, body)| {
+            let (position, orientation) = body.kinematic_target?;
+            Some((id, position, orientation))
+        })
+        .collect()
+}
+
+/// Points the kinematic bodies at the part of the way to their targets
+/// that they should cover in the next substep, out of the given number
+/// left. With a single substep left, they are pointed at the targets
+/// themselves.
+fn aim_kinematic_targets(
+    bodies: &mut RigidBodySet,
+    targets: &[(RigidBodyId, Vec3, Quat)],
+    substeps_left: u32,
+) {
+    let fraction = 1.0 / substeps_left as Real;
+
+    for &(id, position, orientation) in targets {
+        if let Some(body) = bodies.get_mut(id) {
+            body.kinematic_target = Some(if substeps_left == 1 {
+                (position, orientation)
+            } else {
+                (
+                    body.position.lerp(position, fraction),
+                    body.orientation.slerp(orientation, fraction).normalized(),
+                )
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rigid_body::{BodyType, RigidBody};
+
+    use super::*;
+
+    #[test]
+    fn kinematic_target_is_reached_over_all_substeps() {
+        let mut bodies = RigidBodySet::new();
+        let body = bodies.insert(RigidBody::new(1.0).with_body_type(BodyType::Kinematic));
+
+        let half_angle: Real = crate::consts::PI / 4.0;
+        let position = Vec3::new(1.0, 0.0, 0.0);
+        let orientation = Quat::from_rijk(half_angle.cos(), 0.0, half_angle.sin(), 0.0);
+        bodies[body].set_kinematic_target(position, orientation);
+
+        let mut system = PhysicsSystem::new().with_substeps(4);
+        let mut generators = ForceGeneratorSet::new();
+        let duration = 1.0 / 60.0;
+
+        system.start_frame(&mut bodies);
+        system.step(&mut bodies, &mut generators, duration);
+
+        let reached = &bodies[body];
+        assert!((reached.position - position).magnitude() < 1e-5);
+        assert!(reached.orientation.dot(orientation).abs() > 1.0 - 1e-5);
+        assert!((reached.velocity - position / duration).magnitude() < 1e-2);
+
+        // With no new target, the body stays where it is.
+        system.start_frame(&mut bodies);
+        system.step(&mut bodies, &mut generators, duration);
+
+        let stayed = &bodies[body];
+        assert!((stayed.position - position).magnitude() < 1e-5);
+        assert!(stayed.velocity.magnitude() < 1e-3);
+    }
+}
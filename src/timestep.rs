@@ -0,0 +1,72 @@
+use crate::precision::Real;
+
+/// Turns the varying time between rendered frames into a whole
+/// number of fixed steps.
+///
+/// The time of every frame is added to an accumulator, and as many
+/// steps as fit in it are taken out. What is left over is carried to
+/// the next frame, and tells how far the simulation is between its
+/// last two steps, so the rendering can be interpolated.
+///
+/// If the simulation takes longer to run than the time it simulates,
+/// every frame needs more steps than the last, and the program grinds
+/// to a halt. To avoid that, no more than a maximum number of steps
+/// are taken in a frame, and the simulation falls behind instead.
+#[derive(Debug, Clone)]
+pub struct FixedTimestep {
+    step: Real,
+    max_steps: u32,
+    accumulator: Real,
+}
+
+impl FixedTimestep {
+    pub fn new(step: Real) -> Self {
+        assert!(step > 0.0, "The fixed step must be longer than zero");
+        Self {
+            step,
+            max_steps: 8,
+            accumulator: 0.0,
+        }
+    }
+
+    pub fn with_max_steps(mut self, max_steps: u32) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Returns the duration of each step.
+    pub fn step(&self) -> Real {
+        self.step
+    }
+
+    /// Adds the time of a frame, and returns the number of steps to
+    /// take for it.
+    pub fn advance(&mut self, frame_time: Real) -> u32 {
+        self.accumulator += frame_time.max(0.0);
+
+        let mut steps = (self.accumulator / self.step).floor() as u32;
+        if steps > self.max_steps {
+            // Drop the steps we can't afford, but keep the fraction of
+            // a step left over.
+            self.accumulator -= (steps - self.max_steps) as Real * self.step;
+            steps = self.max_steps;
+        }
+
+        self.accumulator -= steps as Real * self.step;
+        self.accumulator = self.accumulator.max(0.0);
+
+        steps
+    }
+
+    /// Returns how far the simulation is between its last step and
+    /// the next one, from zero to one. Rendering should interpolate
+    /// between the last two steps by this much.
+    pub fn alpha(&self) -> Real {
+        (self.accumulator / self.step).clamp(0.0, 1.0)
+    }
+
+    /// Drops any time left over, as when the simulation is reset.
+    pub fn reset(&mut self) {
+        self.accumulator = 0.0;
+    }
+}